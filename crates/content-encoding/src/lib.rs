@@ -0,0 +1,184 @@
+//! HTTP content codings and the `Accept-Encoding` negotiation.
+
+use std::str::FromStr;
+
+/// A content coding that can be applied to the response body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumString, strum::IntoStaticStr)]
+pub enum Encoding {
+    /// The gzip coding.
+    #[strum(serialize = "gzip")]
+    Gzip,
+    /// The brotli coding.
+    #[strum(serialize = "br")]
+    Brotli,
+    /// The zstd coding.
+    #[strum(serialize = "zstd")]
+    Zstd,
+}
+
+impl Encoding {
+    /// The content coding token, as used in the `Content-Encoding` and `Accept-Encoding` headers.
+    pub fn as_str(self) -> &'static str {
+        self.into()
+    }
+
+    /// The content coding token as a header value.
+    pub fn header_value(self) -> http::HeaderValue {
+        http::HeaderValue::from_static(self.as_str())
+    }
+}
+
+/// A comma-separated list of encodings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct List(pub Vec<Encoding>);
+
+impl FromStr for List {
+    type Err = strum::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let list = s
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(Encoding::from_str)
+            .collect::<Result<_, _>>()?;
+        Ok(Self(list))
+    }
+}
+
+/// The quality value in thousandths, as in the `q` parameter of the `Accept-Encoding` header.
+pub type Quality = u16;
+
+/// Find the quality the client, per its `Accept-Encoding` header value, assigns to the given
+/// encoding.
+///
+/// Returns [`None`] when the encoding is not acceptable.
+pub fn quality(accept_encoding: &[u8], encoding: Encoding) -> Option<Quality> {
+    listed_quality(accept_encoding, encoding.as_str().as_bytes()).filter(|quality| *quality > 0)
+}
+
+/// Find the quality the client, per its `Accept-Encoding` header value, assigns to
+/// the `identity` coding, that is to the content as-is, directly or via the wildcard.
+///
+/// Returns [`None`] when the client does not mention it, in which case the content as-is is
+/// acceptable, but any acceptable encoding is preferred.
+pub fn identity_quality(accept_encoding: &[u8]) -> Option<Quality> {
+    listed_quality(accept_encoding, b"identity")
+}
+
+/// Find the quality of the given coding token listed in the `Accept-Encoding` header value,
+/// directly or via the wildcard, including the zero one.
+fn listed_quality(accept_encoding: &[u8], token: &[u8]) -> Option<Quality> {
+    let mut wildcard = None;
+
+    for item in accept_encoding.split(|byte| *byte == b',') {
+        let mut params = item.split(|byte| *byte == b';');
+
+        let coding = params.next().unwrap_or_default().trim_ascii();
+
+        let quality = params
+            .find_map(|param| {
+                let param = param.trim_ascii();
+                param
+                    .strip_prefix(b"q=")
+                    .or_else(|| param.strip_prefix(b"Q="))
+            })
+            // The malformed quality makes the coding not acceptable rather than preferred.
+            .map_or(1000, |value| parse_quality(value).unwrap_or(0));
+
+        if coding.eq_ignore_ascii_case(token) {
+            return Some(quality);
+        }
+
+        if coding == b"*" {
+            wildcard = Some(quality);
+        }
+    }
+
+    wildcard
+}
+
+/// Parse the quality value as defined in the RFC 9110.
+fn parse_quality(value: &[u8]) -> Option<Quality> {
+    let value = std::str::from_utf8(value).ok()?;
+    let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+
+    if frac.len() > 3 || !frac.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    let int: Quality = match int {
+        "0" => 0,
+        "1" if frac.bytes().all(|byte| byte == b'0') => return Some(1000),
+        _ => return None,
+    };
+
+    let frac: Quality = format!("{frac:0<3}").parse().ok()?;
+    Some(int + frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qualities() {
+        let cases: &[(&str, Encoding, Option<Quality>)] = &[
+            ("", Encoding::Gzip, None),
+            ("gzip", Encoding::Gzip, Some(1000)),
+            ("gzip, br", Encoding::Brotli, Some(1000)),
+            ("GZIP", Encoding::Gzip, Some(1000)),
+            ("gzip;q=0.5, br;q=0.8", Encoding::Gzip, Some(500)),
+            ("gzip;q=0.5, br;q=0.8", Encoding::Brotli, Some(800)),
+            ("gzip;q=0", Encoding::Gzip, None),
+            ("*", Encoding::Zstd, Some(1000)),
+            ("*;q=0.1, zstd;q=0", Encoding::Zstd, None),
+            ("deflate, *;q=0.2", Encoding::Brotli, Some(200)),
+            ("gzip;q=1.000", Encoding::Gzip, Some(1000)),
+            ("gzip;q=2", Encoding::Gzip, None),
+            ("gzip;q=1.5", Encoding::Gzip, None),
+            ("gzip;q=0.1234", Encoding::Gzip, None),
+            ("gzip;q=high", Encoding::Gzip, None),
+            ("gzip;q=", Encoding::Gzip, None),
+        ];
+
+        for (accept_encoding, encoding, expected) in cases {
+            assert_eq!(
+                quality(accept_encoding.as_bytes(), *encoding),
+                *expected,
+                "{accept_encoding} for {encoding:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn identity_qualities() {
+        let cases: &[(&str, Option<Quality>)] = &[
+            ("", None),
+            ("gzip", None),
+            ("gzip;q=0.1, identity", Some(1000)),
+            ("gzip, identity;q=0.5", Some(500)),
+            ("gzip, identity;q=0", Some(0)),
+            ("gzip, *;q=0", Some(0)),
+            ("IDENTITY;q=0.3", Some(300)),
+        ];
+
+        for (accept_encoding, expected) in cases {
+            assert_eq!(
+                identity_quality(accept_encoding.as_bytes()),
+                *expected,
+                "{accept_encoding}"
+            );
+        }
+    }
+
+    #[test]
+    fn list_parsing() {
+        assert_eq!(List::from_str("").unwrap(), List(vec![]));
+        assert_eq!(
+            List::from_str("br, gzip,zstd").unwrap(),
+            List(vec![Encoding::Brotli, Encoding::Gzip, Encoding::Zstd])
+        );
+        assert!(List::from_str("deflate").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! The `<base href>` rewriting.
+
+use html5ever::interface::TreeSink as _;
+use html5ever::namespace_url;
+use markup5ever_rcdom as rcdom;
+
+use crate::document;
+
+/// Set the `href` of the first `base` element in the HTML document to the given value,
+/// injecting the element as the first child of the `head` if there is none.
+pub fn set_base_href(html: &[u8], href: &str) -> Vec<u8> {
+    let dom = document::parse(html);
+
+    let href_attr = html5ever::Attribute {
+        name: html5ever::QualName::new(None, html5ever::ns!(), html5ever::local_name!("href")),
+        value: href.into(),
+    };
+
+    if let Some(base) = document::find_element(&dom.document, &html5ever::local_name!("base")) {
+        let rcdom::NodeData::Element { ref attrs, .. } = base.data else {
+            unreachable!("the found node is an element");
+        };
+        let mut attrs = attrs.borrow_mut();
+        match attrs
+            .iter_mut()
+            .find(|attr| attr.name.local == html5ever::local_name!("href"))
+        {
+            Some(attr) => attr.value = href_attr.value,
+            None => attrs.push(href_attr),
+        }
+    } else if let Some(head) =
+        document::find_element(&dom.document, &html5ever::local_name!("head"))
+    {
+        let base = document::create_element(&dom, html5ever::local_name!("base"), vec![href_attr]);
+        let first_child = head.children.borrow().first().map(std::rc::Rc::clone);
+        match first_child {
+            Some(first_child) => dom.append_before_sibling(
+                &first_child,
+                html5ever::interface::NodeOrText::AppendNode(base),
+            ),
+            None => dom.append(&head, html5ever::interface::NodeOrText::AppendNode(base)),
+        }
+    }
+
+    document::serialize(&dom, html.len() + href.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewriting() {
+        let cases: &[(&str, &str)] = &[
+            (
+                "<html><head><title>App</title></head><body></body></html>",
+                r#"<html><head><base href="/tenant-a/"><title>App</title></head><body></body></html>"#,
+            ),
+            (
+                r#"<html><head><base href="/" target="_blank"></head><body></body></html>"#,
+                r#"<html><head><base href="/tenant-a/" target="_blank"></head><body></body></html>"#,
+            ),
+            (
+                r#"<html><head><base target="_blank"></head><body></body></html>"#,
+                r#"<html><head><base target="_blank" href="/tenant-a/"></head><body></body></html>"#,
+            ),
+            (
+                "<html><head></head><body></body></html>",
+                r#"<html><head><base href="/tenant-a/"></head><body></body></html>"#,
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let output = set_base_href(input.as_bytes(), "/tenant-a/");
+            assert_eq!(std::str::from_utf8(&output).unwrap(), *expected, "{input}");
+        }
+    }
+}
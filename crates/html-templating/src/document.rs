@@ -0,0 +1,69 @@
+//! The whole document parsing and serialization for the DOM edits.
+
+use html5ever::namespace_url;
+use html5ever::tendril::TendrilSink as _;
+use markup5ever_rcdom as rcdom;
+
+/// Parse the HTML document.
+pub fn parse(html: &[u8]) -> rcdom::RcDom {
+    let dom = html5ever::parse_document(rcdom::RcDom::default(), Default::default())
+        .from_utf8()
+        .one(html);
+
+    let errors = dom.errors.borrow();
+    if !errors.is_empty() {
+        tracing::warn!(message = "parsing errors", errors = ?*errors);
+    }
+    drop(errors);
+
+    dom
+}
+
+/// Serialize the HTML document.
+pub fn serialize(dom: &rcdom::RcDom, capacity: usize) -> Vec<u8> {
+    let mut output = Vec::with_capacity(capacity);
+
+    let serializable_document: rcdom::SerializableHandle = std::rc::Rc::clone(&dom.document).into();
+
+    html5ever::serialize::serialize(
+        &mut output,
+        &serializable_document,
+        html5ever::serialize::SerializeOpts {
+            scripting_enabled: true,
+            traversal_scope: html5ever::serialize::TraversalScope::ChildrenOnly(None),
+            create_missing_parent: false,
+        },
+    )
+    .unwrap(); // vec write never fails
+
+    output
+}
+
+/// Create an HTML element with the given name and attributes.
+pub fn create_element(
+    dom: &rcdom::RcDom,
+    name: html5ever::LocalName,
+    attrs: Vec<html5ever::Attribute>,
+) -> rcdom::Handle {
+    use html5ever::interface::TreeSink as _;
+
+    dom.create_element(
+        html5ever::QualName::new(None, html5ever::ns!(html), name),
+        attrs,
+        Default::default(),
+    )
+}
+
+/// Find the first HTML element with the given name in the document order.
+pub fn find_element(node: &rcdom::Handle, name: &html5ever::LocalName) -> Option<rcdom::Handle> {
+    if let rcdom::NodeData::Element { name: ref qual, .. } = node.data {
+        if qual.ns == html5ever::ns!(html) && qual.local == *name {
+            return Some(std::rc::Rc::clone(node));
+        }
+    }
+
+    node.children
+        .borrow()
+        .iter()
+        .find_map(|child| find_element(child, name))
+}
@@ -2,13 +2,18 @@
 
 #![allow(missing_docs, clippy::missing_docs_in_private_items)]
 
+mod base_href;
+mod document;
 mod dom;
+mod script;
 
 use html5ever::tendril::TendrilSink as _;
 use markup5ever_rcdom as rcdom;
 use std::borrow::Cow;
 
+pub use base_href::set_base_href;
 pub use dom::{template_element_filter, TemplateElementFilter};
+pub use script::append_script;
 
 /// The HTML teplating processor.
 ///
@@ -0,0 +1,52 @@
+//! The inline script injection.
+
+use html5ever::interface::TreeSink as _;
+
+use crate::document;
+
+/// Append the inline script with the given code to the end of the `body` of the HTML document,
+/// or of the `head` if there is no `body`.
+pub fn append_script(html: &[u8], code: &str) -> Vec<u8> {
+    let dom = document::parse(html);
+
+    let parent = document::find_element(&dom.document, &html5ever::local_name!("body"))
+        .or_else(|| document::find_element(&dom.document, &html5ever::local_name!("head")));
+
+    if let Some(parent) = parent {
+        let script = document::create_element(&dom, html5ever::local_name!("script"), Vec::new());
+        dom.append(
+            &script,
+            html5ever::interface::NodeOrText::AppendText(code.into()),
+        );
+        dom.append(
+            &parent,
+            html5ever::interface::NodeOrText::AppendNode(script),
+        );
+    }
+
+    document::serialize(&dom, html.len() + code.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appending() {
+        let cases: &[(&str, &str)] = &[
+            (
+                "<html><head></head><body><div></div></body></html>",
+                "<html><head></head><body><div></div><script>a < b && reload()</script></body></html>",
+            ),
+            (
+                "<p>fragment",
+                "<html><head></head><body><p>fragment</p><script>a < b && reload()</script></body></html>",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let output = append_script(input.as_bytes(), "a < b && reload()");
+            assert_eq!(std::str::from_utf8(&output).unwrap(), *expected, "{input}");
+        }
+    }
+}
@@ -0,0 +1,444 @@
+//! Redirecting the requests to the canonical origin.
+
+use std::{net::IpAddr, str::FromStr};
+
+/// An error that can occur while parsing the canonicalization settings.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The origin is not a valid URI.
+    #[error("invalid origin {0:?}: {1}")]
+    OriginUri(String, http::uri::InvalidUri),
+
+    /// The origin is not an `http` or `https` scheme and authority with no path.
+    #[error("the origin {0:?} must be an http or https scheme and a host, with no path")]
+    Origin(String),
+
+    /// The network address is invalid.
+    #[error("invalid network address {0:?}")]
+    Addr(String),
+
+    /// The network prefix length is invalid.
+    #[error("invalid network prefix length in {0:?}")]
+    PrefixLen(String),
+}
+
+/// The canonicalization layer: redirects the requests that are not sent to the canonical
+/// origin there.
+#[derive(Debug, Clone)]
+pub struct Canonicalization {
+    /// The origin to redirect to.
+    pub origin: Origin,
+
+    /// The status to redirect with.
+    pub status: http::StatusCode,
+
+    /// The proxies to trust the `Forwarded`, `X-Forwarded-Host` and `X-Forwarded-Proto`
+    /// headers from.
+    pub trusted_proxies: TrustedProxies,
+}
+
+impl Canonicalization {
+    /// Find the location to redirect the request to, if it is not sent to the canonical origin.
+    ///
+    /// The proxy headers are only honored when the peer is a trusted proxy.
+    pub fn redirect<RequestBody>(
+        &self,
+        req: &http::Request<RequestBody>,
+        peer: Option<IpAddr>,
+    ) -> Option<http::HeaderValue> {
+        let trusted = peer.is_some_and(|peer| self.trusted_proxies.contains(peer));
+        let forwarded = trusted.then(|| Forwarded::from_headers(req.headers()));
+
+        let scheme = forwarded
+            .as_ref()
+            .and_then(|forwarded| forwarded.proto.as_deref())
+            .or_else(|| req.uri().scheme_str())
+            .unwrap_or("http");
+
+        let host = forwarded
+            .as_ref()
+            .and_then(|forwarded| forwarded.host.as_deref())
+            .or_else(|| req.uri().authority().map(http::uri::Authority::as_str))
+            .or_else(|| req.headers().get(http::header::HOST)?.to_str().ok());
+
+        if self.origin.matches(scheme, host) {
+            return None;
+        }
+
+        let path_and_query = req
+            .uri()
+            .path_and_query()
+            .map_or("/", http::uri::PathAndQuery::as_str);
+        let location = format!(
+            "{}://{}{path_and_query}",
+            self.origin.scheme, self.origin.authority
+        );
+
+        // The location is built from the parsed URI parts, which are valid header values.
+        http::HeaderValue::try_from(location).ok()
+    }
+}
+
+/// The canonical origin, like `https://www.example.com`.
+#[derive(Debug, Clone)]
+pub struct Origin {
+    /// The scheme.
+    scheme: http::uri::Scheme,
+
+    /// The host and the optional port.
+    authority: http::uri::Authority,
+}
+
+impl FromStr for Origin {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let uri = http::Uri::from_str(s).map_err(|err| Error::OriginUri(s.to_owned(), err))?;
+
+        let http::uri::Parts {
+            scheme: Some(scheme),
+            authority: Some(authority),
+            path_and_query,
+            ..
+        } = uri.into_parts()
+        else {
+            return Err(Error::Origin(s.to_owned()));
+        };
+
+        let is_http = scheme == http::uri::Scheme::HTTP || scheme == http::uri::Scheme::HTTPS;
+        let has_path = path_and_query.is_some_and(|path_and_query| path_and_query != "/");
+        if !is_http || has_path || authority.as_str().contains('@') {
+            return Err(Error::Origin(s.to_owned()));
+        }
+
+        Ok(Self { scheme, authority })
+    }
+}
+
+impl Origin {
+    /// Check whether the request scheme and host match the origin.
+    fn matches(&self, scheme: &str, host: Option<&str>) -> bool {
+        if !scheme.eq_ignore_ascii_case(self.scheme.as_str()) {
+            return false;
+        }
+
+        let Some(host) = host.and_then(|host| http::uri::Authority::from_str(host).ok()) else {
+            return false;
+        };
+
+        let default_port = default_port(&self.scheme);
+        host.host()
+            .trim_end_matches('.')
+            .eq_ignore_ascii_case(self.authority.host())
+            && host.port_u16().unwrap_or(default_port)
+                == self.authority.port_u16().unwrap_or(default_port)
+    }
+}
+
+/// The default port for the scheme.
+fn default_port(scheme: &http::uri::Scheme) -> u16 {
+    if *scheme == http::uri::Scheme::HTTPS {
+        443
+    } else {
+        80
+    }
+}
+
+/// The forwarded request details reported by the proxy.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Forwarded {
+    /// The original request scheme.
+    proto: Option<String>,
+
+    /// The original request host.
+    host: Option<String>,
+}
+
+impl Forwarded {
+    /// Read the details from the `Forwarded` header, falling back to the `X-Forwarded-Proto`
+    /// and `X-Forwarded-Host` headers.
+    ///
+    /// Only the last entry of the lists is used, as it is appended by the trusted proxy, while
+    /// the ones before it come from the client.
+    fn from_headers(headers: &http::HeaderMap) -> Self {
+        let mut forwarded = Self::default();
+
+        if let Some(last) = last_entry(headers, &http::header::FORWARDED) {
+            for pair in last.split(';') {
+                let Some((key, value)) = pair.split_once('=') else {
+                    continue;
+                };
+                let value = value.trim().trim_matches('"').to_owned();
+                match key.trim().to_ascii_lowercase().as_str() {
+                    "proto" => forwarded.proto = Some(value),
+                    "host" => forwarded.host = Some(value),
+                    _ => {}
+                }
+            }
+        }
+
+        if forwarded.proto.is_none() {
+            forwarded.proto = last_entry(
+                headers,
+                &http::header::HeaderName::from_static("x-forwarded-proto"),
+            )
+            .map(ToOwned::to_owned);
+        }
+        if forwarded.host.is_none() {
+            forwarded.host = last_entry(
+                headers,
+                &http::header::HeaderName::from_static("x-forwarded-host"),
+            )
+            .map(ToOwned::to_owned);
+        }
+
+        forwarded
+    }
+}
+
+/// The last entry of the comma-separated list header, which can span multiple lines.
+fn last_entry<'a>(headers: &'a http::HeaderMap, name: &http::HeaderName) -> Option<&'a str> {
+    let value = headers.get_all(name).iter().next_back()?.to_str().ok()?;
+    let last = value.rsplit(',').next()?.trim();
+    (!last.is_empty()).then_some(last)
+}
+
+/// The list of the trusted proxy networks.
+#[derive(Debug, Default, Clone)]
+pub struct TrustedProxies(pub Vec<Cidr>);
+
+impl FromStr for TrustedProxies {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl TrustedProxies {
+    /// Check whether the address belongs to any of the trusted networks.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        self.0.iter().any(|cidr| cidr.contains(addr))
+    }
+}
+
+/// A network in the CIDR notation, like `10.0.0.0/8`, or a single address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    /// The network address.
+    addr: IpAddr,
+
+    /// The network prefix length.
+    prefix_len: u8,
+}
+
+impl FromStr for Cidr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (s, None),
+        };
+
+        let addr: IpAddr = addr.parse().map_err(|_| Error::Addr(s.to_owned()))?;
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .parse()
+                .ok()
+                .filter(|prefix_len| *prefix_len <= max_len)
+                .ok_or_else(|| Error::PrefixLen(s.to_owned()))?,
+            None => max_len,
+        };
+
+        Ok(Self { addr, prefix_len })
+    }
+}
+
+impl Cidr {
+    /// Check whether the address belongs to the network.
+    ///
+    /// The IPv4-mapped IPv6 addresses are matched as IPv4 ones.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => prefix_eq(
+                network.to_bits().into(),
+                addr.to_bits().into(),
+                32,
+                self.prefix_len,
+            ),
+            (IpAddr::V6(network), IpAddr::V6(addr)) => {
+                prefix_eq(network.to_bits(), addr.to_bits(), 128, self.prefix_len)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Check whether the leading `prefix_len` bits of the `bits`-wide addresses are equal.
+fn prefix_eq(network: u128, addr: u128, bits: u8, prefix_len: u8) -> bool {
+    let shift = bits - prefix_len;
+    shift >= bits || network >> shift == addr >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonicalization() -> Canonicalization {
+        Canonicalization {
+            origin: "https://www.example.com".parse().unwrap(),
+            status: http::StatusCode::MOVED_PERMANENTLY,
+            trusted_proxies: "10.0.0.0/8, ::1".parse().unwrap(),
+        }
+    }
+
+    type Headers<'a> = &'a [(&'static str, &'static str)];
+
+    fn request(headers: Headers) -> http::Request<()> {
+        let mut builder = http::Request::builder().uri("/page?a=1");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn redirects() {
+        let canonicalization = canonicalization();
+        let proxy: Option<IpAddr> = Some("10.1.2.3".parse().unwrap());
+        let client: Option<IpAddr> = Some("192.0.2.1".parse().unwrap());
+        let target = Some("https://www.example.com/page?a=1");
+
+        let cases: &[(Headers, Option<IpAddr>, Option<&str>)] = &[
+            (&[("host", "www.example.com")], client, target),
+            (&[("host", "example.com")], proxy, target),
+            (
+                &[
+                    ("host", "sap:8080"),
+                    ("x-forwarded-proto", "https"),
+                    ("x-forwarded-host", "www.example.com"),
+                ],
+                proxy,
+                None,
+            ),
+            (
+                &[
+                    ("host", "sap:8080"),
+                    ("x-forwarded-proto", "http, https"),
+                    ("x-forwarded-host", "WWW.example.com:443"),
+                ],
+                proxy,
+                None,
+            ),
+            (
+                &[
+                    ("host", "sap:8080"),
+                    ("x-forwarded-proto", "https, http"),
+                    ("x-forwarded-host", "www.example.com"),
+                ],
+                proxy,
+                target,
+            ),
+            (
+                &[
+                    ("host", "sap:8080"),
+                    ("x-forwarded-proto", "https"),
+                    ("x-forwarded-host", "www.example.com, evil.example"),
+                ],
+                proxy,
+                target,
+            ),
+            (
+                &[
+                    ("host", "sap:8080"),
+                    ("x-forwarded-proto", "http"),
+                    ("x-forwarded-host", "www.example.com"),
+                ],
+                proxy,
+                target,
+            ),
+            (
+                &[
+                    ("host", "sap:8080"),
+                    (
+                        "forwarded",
+                        "for=192.0.2.1;proto=http, for=10.1.2.3;proto=https;host=\"www.example.com\"",
+                    ),
+                    ("x-forwarded-proto", "http"),
+                ],
+                proxy,
+                None,
+            ),
+            (
+                &[
+                    ("host", "sap:8080"),
+                    ("forwarded", "for=192.0.2.1;proto=https;host=www.example.com"),
+                    ("forwarded", "for=10.1.2.3;proto=http;host=www.example.com"),
+                ],
+                proxy,
+                target,
+            ),
+            (
+                &[
+                    ("host", "www.example.com"),
+                    ("x-forwarded-proto", "https"),
+                    ("x-forwarded-host", "www.example.com"),
+                ],
+                client,
+                target,
+            ),
+            (
+                &[("host", "www.example.com"), ("x-forwarded-proto", "https")],
+                None,
+                target,
+            ),
+            (
+                &[("host", "www.example.com"), ("x-forwarded-proto", "https")],
+                Some("::ffff:10.0.0.1".parse().unwrap()),
+                None,
+            ),
+            (&[("x-forwarded-proto", "https")], proxy, target),
+        ];
+
+        for (headers, peer, expected) in cases {
+            assert_eq!(
+                canonicalization.redirect(&request(headers), *peer),
+                expected.map(http::HeaderValue::from_static),
+                "{headers:?} {peer:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parsing() {
+        assert!(Origin::from_str("https://www.example.com/").is_ok());
+        assert!(Origin::from_str("http://localhost:8080").is_ok());
+        assert!(Origin::from_str("www.example.com").is_err());
+        assert!(Origin::from_str("ftp://example.com").is_err());
+        assert!(Origin::from_str("https://example.com/path").is_err());
+
+        let cidr = Cidr::from_str("192.168.0.0/16").unwrap();
+        assert!(cidr.contains("192.168.10.1".parse().unwrap()));
+        assert!(!cidr.contains("192.169.0.1".parse().unwrap()));
+        assert!(!cidr.contains("::1".parse().unwrap()));
+
+        let any = Cidr::from_str("0.0.0.0/0").unwrap();
+        assert!(any.contains("203.0.113.7".parse().unwrap()));
+
+        let v6 = Cidr::from_str("fd00::/8").unwrap();
+        assert!(v6.contains("fd12::1".parse().unwrap()));
+        assert!(!v6.contains("fe80::1".parse().unwrap()));
+
+        assert!(Cidr::from_str("10.0.0.0/33").is_err());
+        assert!(Cidr::from_str("10.0.0/8").is_err());
+        assert!(TrustedProxies::from_str("").unwrap().0.is_empty());
+    }
+}
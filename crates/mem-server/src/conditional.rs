@@ -0,0 +1,125 @@
+//! Conditional requests handling.
+//!
+//! See <https://www.rfc-editor.org/rfc/rfc9110#name-conditional-requests>.
+
+use http::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+
+/// Check whether the response to the request with the given headers can be replaced with
+/// the `304 Not Modified`.
+///
+/// The `If-None-Match` takes precedence over the `If-Modified-Since`, and the latter is only
+/// evaluated when the former is absent.
+pub fn is_not_modified(req_headers: &http::HeaderMap, res_headers: &http::HeaderMap) -> bool {
+    if let Some(if_none_match) = req_headers.get(IF_NONE_MATCH) {
+        let Some(etag) = res_headers.get(ETAG) else {
+            return false;
+        };
+        return if_none_match_matches(if_none_match.as_bytes(), etag.as_bytes());
+    }
+
+    if let Some(if_modified_since) = req_headers.get(IF_MODIFIED_SINCE) {
+        let Some(last_modified) = res_headers.get(LAST_MODIFIED) else {
+            return false;
+        };
+        let Some(if_modified_since) = parse_http_date(if_modified_since) else {
+            return false;
+        };
+        let Some(last_modified) = parse_http_date(last_modified) else {
+            return false;
+        };
+        return last_modified <= if_modified_since;
+    }
+
+    false
+}
+
+/// Turn the given response parts into the parts of the `304 Not Modified` response.
+///
+/// Drops the representation headers that describe the omitted content, and keeps the rest
+/// (like `ETag`, `Cache-Control` and `Vary`) as required.
+pub fn make_not_modified(parts: &mut http::response::Parts) {
+    parts.status = http::StatusCode::NOT_MODIFIED;
+    parts.headers.remove(http::header::CONTENT_TYPE);
+    parts.headers.remove(http::header::CONTENT_LENGTH);
+}
+
+/// Evaluate the `If-None-Match` header value against the entity tag using the weak comparison.
+fn if_none_match_matches(if_none_match: &[u8], etag: &[u8]) -> bool {
+    let if_none_match = if_none_match.trim_ascii();
+    if if_none_match == b"*" {
+        return true;
+    }
+
+    let etag = opaque_tag(etag.trim_ascii());
+
+    if_none_match
+        .split(|byte| *byte == b',')
+        .map(|candidate| opaque_tag(candidate.trim_ascii()))
+        .any(|candidate| candidate == etag)
+}
+
+/// Strip the weakness indicator from the entity tag, leaving the opaque tag for comparison.
+fn opaque_tag(etag: &[u8]) -> &[u8] {
+    etag.strip_prefix(b"W/").unwrap_or(etag)
+}
+
+/// Parse the HTTP date from the header value.
+fn parse_http_date(val: &http::HeaderValue) -> Option<std::time::SystemTime> {
+    let val = val.to_str().ok()?;
+    httpdate::parse_http_date(val).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn if_none_match() {
+        let cases: &[(&str, &str, bool)] = &[
+            (r#""a""#, r#""a""#, true),
+            (r#""b""#, r#""a""#, false),
+            (r#"*"#, r#""a""#, true),
+            (r#"W/"a""#, r#""a""#, true),
+            (r#""x", "a""#, r#""a""#, true),
+            (r#""x","y""#, r#""a""#, false),
+        ];
+
+        for (if_none_match, etag, expected) in cases {
+            assert_eq!(
+                if_none_match_matches(if_none_match.as_bytes(), etag.as_bytes()),
+                *expected,
+                "{if_none_match} vs {etag}"
+            );
+        }
+    }
+
+    #[test]
+    fn if_modified_since() {
+        let mut res_headers = http::HeaderMap::new();
+        res_headers.insert(
+            LAST_MODIFIED,
+            http::HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+
+        let cases: &[(&str, bool)] = &[
+            ("Wed, 21 Oct 2015 07:28:00 GMT", true),
+            ("Thu, 22 Oct 2015 07:28:00 GMT", true),
+            ("Tue, 20 Oct 2015 07:28:00 GMT", false),
+            ("garbage", false),
+        ];
+
+        for (if_modified_since, expected) in cases {
+            let mut req_headers = http::HeaderMap::new();
+            req_headers.insert(
+                IF_MODIFIED_SINCE,
+                http::HeaderValue::from_str(if_modified_since).unwrap(),
+            );
+
+            assert_eq!(
+                is_not_modified(&req_headers, &res_headers),
+                *expected,
+                "{if_modified_since}"
+            );
+        }
+    }
+}
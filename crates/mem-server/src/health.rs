@@ -0,0 +1,236 @@
+//! The health endpoints, answered ahead of everything else.
+
+use crate::{Body, Readiness};
+
+/// The default path to report the liveness at.
+pub const DEFAULT_LIVENESS_PATH: &str = "/__sap/healthz";
+
+/// The default path to report the readiness at.
+pub const DEFAULT_READINESS_PATH: &str = "/__sap/readyz";
+
+/// The endpoints to report the health of the server at.
+///
+/// The endpoints are answered before the virtual hosts selection, the canonical redirects and
+/// the routes lookup, so the probes get the same answer regardless of the host they use.
+#[derive(Debug, Clone)]
+pub struct Health {
+    /// The path to report the liveness at, which is always successful while serving.
+    pub liveness_path: String,
+
+    /// The path to report the readiness at.
+    pub readiness_path: String,
+
+    /// The readiness to report.
+    pub readiness: Readiness,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            liveness_path: DEFAULT_LIVENESS_PATH.to_owned(),
+            readiness_path: DEFAULT_READINESS_PATH.to_owned(),
+            readiness: Readiness::default(),
+        }
+    }
+}
+
+impl Health {
+    /// The paths of the endpoints.
+    pub fn paths(&self) -> [&str; 2] {
+        [&self.liveness_path, &self.readiness_path]
+    }
+
+    /// Respond to the request if it is for one of the endpoints.
+    pub(crate) fn respond<RequestBody>(
+        &self,
+        req: &http::Request<RequestBody>,
+    ) -> Option<http::Response<Body>> {
+        let path = req.uri().path();
+        let (ok, reload_failed) = if path == self.liveness_path {
+            (true, false)
+        } else if path == self.readiness_path {
+            (self.readiness.is_ready(), self.readiness.is_reload_failed())
+        } else {
+            return None;
+        };
+
+        let method = req.method();
+        if method != http::Method::GET && method != http::Method::HEAD {
+            let (status, body) = if method == http::Method::OPTIONS {
+                (http::StatusCode::NO_CONTENT, Body::None)
+            } else {
+                (
+                    http::StatusCode::METHOD_NOT_ALLOWED,
+                    Body::Full(crate::empty_bytes()),
+                )
+            };
+            let mut res = http::Response::new(body);
+            *res.status_mut() = status;
+            res.headers_mut().insert(
+                http::header::ALLOW,
+                http::HeaderValue::from_static("GET, HEAD"),
+            );
+            return Some(res);
+        }
+
+        // The failed reload is reported alongside, as the previous site is still served.
+        let (status, body) = match (ok, reload_failed) {
+            (true, false) => (http::StatusCode::OK, "ok\n"),
+            (true, true) => (http::StatusCode::OK, "ok\nreload: failed\n"),
+            (false, _) => (http::StatusCode::SERVICE_UNAVAILABLE, "not ready\n"),
+        };
+
+        let mut res = http::Response::new(Body::Full(bytes::Bytes::from_static(body.as_bytes())));
+        *res.status_mut() = status;
+        let headers = res.headers_mut();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static("text/plain; charset=utf-8"),
+        );
+        headers.insert(
+            http::header::CACHE_CONTROL,
+            http::HeaderValue::from_static("no-store"),
+        );
+        Some(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(health: &Health, path: &str) -> Option<bytes::Bytes> {
+        let req = http::Request::builder().uri(path).body(()).unwrap();
+        health.respond(&req).map(|res| match res.into_body() {
+            Body::Full(body) => body,
+            _ => unreachable!(),
+        })
+    }
+
+    fn get(health: &Health, method: http::Method, path: &str) -> Option<http::StatusCode> {
+        let req = http::Request::builder()
+            .method(method)
+            .uri(path)
+            .body(())
+            .unwrap();
+        health.respond(&req).map(|res| res.status())
+    }
+
+    #[test]
+    fn liveness() {
+        let health = Health::default();
+        assert_eq!(
+            get(&health, http::Method::GET, "/__sap/healthz"),
+            Some(http::StatusCode::OK)
+        );
+    }
+
+    #[test]
+    fn readiness() {
+        let health = Health::default();
+        assert_eq!(
+            get(&health, http::Method::GET, "/__sap/readyz"),
+            Some(http::StatusCode::SERVICE_UNAVAILABLE)
+        );
+
+        health.readiness.set_loaded(true);
+        assert_eq!(
+            get(&health, http::Method::GET, "/__sap/readyz"),
+            Some(http::StatusCode::OK)
+        );
+
+        health.readiness.set_stopping();
+        assert_eq!(
+            get(&health, http::Method::GET, "/__sap/readyz"),
+            Some(http::StatusCode::SERVICE_UNAVAILABLE)
+        );
+        assert_eq!(
+            get(&health, http::Method::GET, "/__sap/healthz"),
+            Some(http::StatusCode::OK)
+        );
+    }
+
+    #[test]
+    fn failed_reload() {
+        let health = Health::default();
+        health.readiness.set_loaded(true);
+        health.readiness.set_reload_failed(true);
+        assert_eq!(
+            get(&health, http::Method::GET, "/__sap/readyz"),
+            Some(http::StatusCode::OK)
+        );
+        assert_eq!(
+            body(&health, "/__sap/readyz").as_deref(),
+            Some(&b"ok\nreload: failed\n"[..])
+        );
+        assert_eq!(
+            body(&health, "/__sap/healthz").as_deref(),
+            Some(&b"ok\n"[..])
+        );
+
+        health.readiness.set_reload_failed(false);
+        assert_eq!(
+            body(&health, "/__sap/readyz").as_deref(),
+            Some(&b"ok\n"[..])
+        );
+    }
+
+    #[test]
+    fn other_methods() {
+        let health = Health::default();
+        assert_eq!(
+            get(&health, http::Method::POST, "/__sap/healthz"),
+            Some(http::StatusCode::METHOD_NOT_ALLOWED)
+        );
+        assert_eq!(
+            get(&health, http::Method::OPTIONS, "/__sap/readyz"),
+            Some(http::StatusCode::NO_CONTENT)
+        );
+    }
+
+    #[test]
+    fn collisions() {
+        let mut server = crate::MemServer::default();
+        server.routes.insert(
+            "/__sap/readyz".into(),
+            http::Response::new(bytes::Bytes::from_static(b"file")).into(),
+        );
+        server.routes.insert(
+            "/".into(),
+            http::Response::new(bytes::Bytes::from_static(b"root")).into(),
+        );
+
+        let health = Health::default();
+        assert_eq!(server.health_collisions(&health), ["/__sap/readyz"]);
+        assert_eq!(server.shadowed_routes(["/", "/__sap/live-reload"]), ["/"]);
+    }
+
+    #[test]
+    fn ahead_of_everything() {
+        let health = Health::default();
+        health.readiness.set_loaded(true);
+
+        let server = crate::MemServer {
+            canonicalization: Some(crate::Canonicalization {
+                origin: "https://www.example.com".parse().unwrap(),
+                status: http::StatusCode::MOVED_PERMANENTLY,
+                trusted_proxies: Default::default(),
+            }),
+            health: Some(health),
+            ..Default::default()
+        };
+
+        let req = http::Request::builder()
+            .uri("http://10.0.0.1:8080/__sap/readyz")
+            .body(())
+            .unwrap();
+        assert_eq!(server.handle_request(req).status(), http::StatusCode::OK);
+    }
+
+    #[test]
+    fn other_paths() {
+        let health = Health::default();
+        assert_eq!(get(&health, http::Method::GET, "/"), None);
+        assert_eq!(get(&health, http::Method::GET, "/__sap/healthz/"), None);
+    }
+}
@@ -1,61 +1,515 @@
 //! An server that preloads the responses for the given routes in-memory and then serves them right
 //! from the memory without touching the disk/fs.
 
-use std::collections::HashMap;
+mod canonical;
+mod conditional;
+mod health;
+mod methods;
+mod navigation;
+mod path;
+mod range;
+mod readiness;
+mod redirects;
+mod route_table;
+mod url_policy;
+mod virtual_hosts;
+
+pub use canonical::{
+    Canonicalization, Cidr, Error as CanonicalizationError, Origin as CanonicalOrigin,
+    TrustedProxies,
+};
+pub use health::{Health, DEFAULT_LIVENESS_PATH, DEFAULT_READINESS_PATH};
+pub use methods::{AllowedMethods, Error as AllowedMethodsError};
+pub use navigation::{AssetPrefixes, Error as AssetPrefixesError, NavigationFilter};
+pub use path::{normalize as normalize_path, Error as PathError};
+pub use readiness::Readiness;
+pub use redirects::{
+    is_redirect as is_redirect_status, Action as RedirectAction, Error as RedirectError,
+    Pattern as RedirectPattern, Redirects, Rule as RedirectRule,
+};
+pub use route_table::RouteTable;
+pub use url_policy::{TrailingSlash, UrlPolicy};
+pub use virtual_hosts::VirtualHosts;
+
+use std::sync::Arc;
 
 use bytes::Bytes;
 
 /// A memory server.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct MemServer {
-    /// The routes to serve.
+    /// The routes to serve, including the one to present when no other route matches.
+    pub routes: RouteTable,
+
+    /// The HTTP methods to respond to.
+    pub allowed_methods: AllowedMethods,
+
+    /// The rules for the requests to get the fallback response when no route matches exactly.
+    pub navigation: NavigationFilter,
+
+    /// The redirect and rewrite rules, evaluated before the routes lookup.
+    pub redirects: Redirects,
+
+    /// The policy for the URL forms the routes are reachable at, applied before the redirect
+    /// and rewrite rules.
+    pub url_policy: UrlPolicy,
+
+    /// The servers for the other hosts, with this one serving the requests to the hosts
+    /// that are not listed.
+    pub virtual_hosts: VirtualHosts,
+
+    /// The redirects to the canonical origin, applied before anything but the health
+    /// endpoints.
+    pub canonicalization: Option<Canonicalization>,
+
+    /// The health endpoints, answered before anything else.
+    pub health: Option<Health>,
+}
+
+/// The body of the response produced by the [`MemServer`].
+#[derive(Debug)]
+pub enum Body {
+    /// No body at all, as opposed to an empty body.
+    None,
+
+    /// The body consisting of a single chunk.
+    Full(Bytes),
+
+    /// The body consisting of multiple chunks to be sent one after another.
+    Chunks(Vec<Bytes>),
+}
+
+impl Body {
+    /// The total length of the body.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Full(bytes) => bytes.len(),
+            Self::Chunks(chunks) => chunks.iter().map(Bytes::len).sum(),
+        }
+    }
+
+    /// Whether the body has no data.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A route with all of its response representations.
+#[derive(Debug, Clone)]
+pub struct Route {
+    /// The response with the content as-is.
+    pub identity: Representation,
+
+    /// The responses with the encoded content.
     ///
-    /// Only exact matches are respected.
-    pub routes: HashMap<String, http::Response<Bytes>>,
+    /// When the client accepts more than one of the encodings with the same preference,
+    /// the one that comes first is used.
+    pub encoded: Vec<(content_encoding::Encoding, Representation)>,
+}
+
+/// A prepared response representation of a route.
+///
+/// The head is shared behind an [`Arc`] and copied on write, so serving the representation
+/// as-is only bumps the refcounts of the head and the body.
+#[derive(Debug, Clone)]
+pub struct Representation {
+    /// The status and the headers.
+    head: Arc<http::response::Parts>,
+
+    /// The content.
+    body: Bytes,
+}
+
+impl Representation {
+    /// The status.
+    pub fn status(&self) -> http::StatusCode {
+        self.head.status
+    }
+
+    /// The status for modification.
+    pub fn status_mut(&mut self) -> &mut http::StatusCode {
+        &mut Arc::make_mut(&mut self.head).status
+    }
+
+    /// The headers.
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.head.headers
+    }
+
+    /// The headers for modification.
+    pub fn headers_mut(&mut self) -> &mut http::HeaderMap {
+        &mut Arc::make_mut(&mut self.head).headers
+    }
+
+    /// The content.
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+}
+
+impl From<http::Response<Bytes>> for Representation {
+    fn from(res: http::Response<Bytes>) -> Self {
+        let (head, body) = res.into_parts();
+        Self {
+            head: Arc::new(head),
+            body,
+        }
+    }
+}
+
+/// A response produced by the [`MemServer`].
+///
+/// The head of the served route representation is shared with the route table, and only
+/// copied when the response needs the changes, like for the partial content.
+#[derive(Debug)]
+pub struct Response {
+    /// The status and the headers.
+    head: Arc<http::response::Parts>,
+
+    /// The body.
+    body: Body,
+}
 
-    /// The response to present when the routes do not have a matching path.
-    pub not_found: Option<http::Response<Bytes>>,
+impl Response {
+    /// The status.
+    pub fn status(&self) -> http::StatusCode {
+        self.head.status
+    }
+
+    /// The headers.
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.head.headers
+    }
+
+    /// The headers for modification.
+    pub fn headers_mut(&mut self) -> &mut http::HeaderMap {
+        &mut Arc::make_mut(&mut self.head).headers
+    }
+
+    /// The body.
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+
+    /// Split into the head, which may still be shared with the route table, and the body.
+    pub fn into_parts(self) -> (Arc<http::response::Parts>, Body) {
+        (self.head, self.body)
+    }
+}
+
+impl From<http::Response<Body>> for Response {
+    fn from(res: http::Response<Body>) -> Self {
+        let (head, body) = res.into_parts();
+        Self {
+            head: Arc::new(head),
+            body,
+        }
+    }
+}
+
+impl Route {
+    /// Select the response representation that best fits the given `Accept-Encoding` request
+    /// header value.
+    ///
+    /// Falls back to the identity representation if none of the encoded ones are acceptable,
+    /// or if the client prefers the `identity` coding over them.
+    pub fn select(&self, accept_encoding: Option<&http::HeaderValue>) -> &Representation {
+        let Some(accept_encoding) = accept_encoding else {
+            return &self.identity;
+        };
+
+        // The encoded representations win the ties against the identity one, and the identity
+        // one not mentioned by the client loses to any acceptable encoding.
+        let identity_quality =
+            content_encoding::identity_quality(accept_encoding.as_bytes()).unwrap_or(0);
+
+        let mut selected: Option<(content_encoding::Quality, &Representation)> = None;
+
+        for (encoding, res) in &self.encoded {
+            let Some(quality) = content_encoding::quality(accept_encoding.as_bytes(), *encoding)
+            else {
+                continue;
+            };
+
+            if quality < identity_quality {
+                continue;
+            }
+
+            if selected.is_none_or(|(selected_quality, _)| quality > selected_quality) {
+                selected = Some((quality, res));
+            }
+        }
+
+        selected.map_or(&self.identity, |(_, res)| res)
+    }
+
+    /// Iterate over all the response representations.
+    pub fn responses_mut(&mut self) -> impl Iterator<Item = &mut Representation> {
+        std::iter::once(&mut self.identity).chain(self.encoded.iter_mut().map(|(_, res)| res))
+    }
+
+    /// Build the headers that do not depend on the request ahead of time, so that serving
+    /// the route as-is only requires sharing the prepared representation.
+    fn prepare(&mut self) {
+        for res in self.responses_mut() {
+            if res.status() == http::StatusCode::OK {
+                res.headers_mut().insert(
+                    http::header::ACCEPT_RANGES,
+                    http::HeaderValue::from_static("bytes"),
+                );
+            } else {
+                res.headers_mut().remove(http::header::ACCEPT_RANGES);
+            }
+        }
+    }
+}
+
+impl From<http::Response<Bytes>> for Route {
+    fn from(identity: http::Response<Bytes>) -> Self {
+        Self {
+            identity: identity.into(),
+            encoded: Vec::new(),
+        }
+    }
 }
 
 impl MemServer {
     /// Handle an incoming HTTP request and provide an HTTP response.
-    pub fn handle_request<RequestBody, ResponseBody>(
+    pub fn handle_request<RequestBody>(&self, req: http::Request<RequestBody>) -> Response {
+        self.handle_request_from(req, None)
+    }
+
+    /// Handle an incoming HTTP request from the given peer and provide an HTTP response.
+    ///
+    /// The peer address is used to tell whether the proxy headers can be trusted.
+    pub fn handle_request_from<RequestBody>(
         &self,
         req: http::Request<RequestBody>,
-    ) -> http::Response<ResponseBody>
-    where
-        ResponseBody: From<Bytes>,
-    {
-        if req.method() != http::Method::GET {
-            let mut res = http::Response::new(empty_bytes().into());
-            *res.status_mut() = http::StatusCode::METHOD_NOT_ALLOWED;
-            return res;
+        peer: Option<std::net::IpAddr>,
+    ) -> Response {
+        let health = self.health.as_ref().and_then(|health| health.respond(&req));
+
+        if health.is_none() {
+            if let Some(server) = self.virtual_hosts.select(&req) {
+                return server.handle_request_from(req, peer);
+            }
         }
 
-        if let Some(res) = self.handle_path(req.uri().path()) {
-            let (parts, body) = res.into_parts();
-            return http::Response::from_parts(parts, body.into());
+        let mut res = health
+            .map(Response::from)
+            .or_else(|| {
+                let canonicalization = self.canonicalization.as_ref()?;
+                let location = canonicalization.redirect(&req, peer)?;
+                Some(redirect(canonicalization.status, location))
+            })
+            .unwrap_or_else(|| self.respond(&req));
+
+        if req.method() == http::Method::HEAD {
+            let body = std::mem::replace(&mut res.body, Body::None);
+            // Report the length of the body the GET would have, unless there would be no body.
+            if !matches!(body, Body::None) {
+                res.headers_mut().insert(
+                    http::header::CONTENT_LENGTH,
+                    http::HeaderValue::from(body.len()),
+                );
+            }
         }
 
-        let mut res = http::Response::new(empty_bytes().into());
-        *res.status_mut() = http::StatusCode::NOT_FOUND;
         res
     }
 
-    /// Handle an incoming request for a given path and provide the response.
-    pub fn handle_path(&self, path: &str) -> Option<http::Response<Bytes>> {
-        for (route_path, route_res) in &self.routes {
-            if route_path == path {
-                return Some(route_res.clone());
+    /// Prepare the response to the request as if it was sent in full.
+    fn respond<RequestBody>(&self, req: &http::Request<RequestBody>) -> Response {
+        let method = req.method();
+
+        if !self.allowed_methods.contains(method) {
+            let mut res = self.error_response(req, http::StatusCode::METHOD_NOT_ALLOWED);
+            res.headers_mut()
+                .insert(http::header::ALLOW, self.allowed_methods.allow().clone());
+            return res;
+        }
+
+        if method == http::Method::OPTIONS {
+            let mut res = http::Response::new(Body::None);
+            *res.status_mut() = http::StatusCode::NO_CONTENT;
+            res.headers_mut()
+                .insert(http::header::ALLOW, self.allowed_methods.allow().clone());
+            return res.into();
+        }
+
+        self.handle_get(req)
+    }
+
+    /// Handle an incoming `GET` request.
+    fn handle_get<RequestBody>(&self, req: &http::Request<RequestBody>) -> Response {
+        let Ok(path) = path::normalize(req.uri().path()) else {
+            return self.error_response(req, http::StatusCode::BAD_REQUEST);
+        };
+        let path = path.as_ref();
+        let query = req.uri().query();
+
+        if let Some(location) = self.url_policy.redirect(path, query, &self.routes) {
+            return redirect(http::StatusCode::MOVED_PERMANENTLY, location);
+        }
+
+        let exact_route = self.url_policy.lookup(path, &self.routes);
+
+        match self.redirects.evaluate(path, query, exact_route.is_some()) {
+            Some(RedirectAction::Redirect(status, location)) => return redirect(status, location),
+            Some(RedirectAction::Rewrite(status, target)) => {
+                if let Some(route) = self.lookup_rewrite(&target) {
+                    return self.serve_route(req, route, status);
+                }
             }
+            None => {}
         }
 
-        if let Some(not_found_res) = &self.not_found {
-            return Some(not_found_res.clone());
+        let maybe_route = match exact_route {
+            Some(route) => Some(route),
+            None if self.navigation.is_navigation(path, req.headers()) => {
+                self.routes.lookup_fallback(path)
+            }
+            None => None,
+        };
+
+        if let Some(route) = maybe_route {
+            let status = route.identity.status();
+            return self.serve_route(req, route, status);
         }
 
-        None
+        self.error_response(req, http::StatusCode::NOT_FOUND)
+    }
+
+    /// Find the rewrite rules targets that do not resolve to any route.
+    ///
+    /// Only the targets without the placeholders can be checked ahead of time.
+    pub fn unresolved_rewrites(&self) -> Vec<&str> {
+        self.redirects
+            .0
+            .iter()
+            .filter(|rule| rule.is_rewrite() && !rule.to.contains(':'))
+            .map(|rule| rule.to.as_str())
+            .filter(|target| self.lookup_rewrite(target).is_none())
+            .collect()
     }
+
+    /// Find the health endpoints paths that shadow the routes.
+    pub fn health_collisions<'a>(&self, health: &'a Health) -> Vec<&'a str> {
+        self.shadowed_routes(health.paths())
+    }
+
+    /// Find the paths, answered ahead of the server, that shadow the routes.
+    pub fn shadowed_routes<'a>(&self, paths: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+        paths
+            .into_iter()
+            .filter(|path| self.url_policy.lookup(path, &self.routes).is_some())
+            .collect()
+    }
+
+    /// Find the route for the rewrite target.
+    ///
+    /// The query is ignored, the path is normalized like the request paths, and
+    /// the `index.html` targets resolve to their directory routes, like the loader maps them.
+    fn lookup_rewrite(&self, target: &str) -> Option<&Route> {
+        let target = target.split_once('?').map_or(target, |(path, _)| path);
+        let target = path::normalize(target).ok()?;
+        let target = target.as_ref();
+
+        if let Some(route) = self.url_policy.lookup(target, &self.routes) {
+            return Some(route);
+        }
+
+        let dir = target.strip_suffix("index.html")?;
+        let dir = dir.strip_suffix('/').unwrap_or(dir);
+        self.routes.get(if dir.is_empty() { "/" } else { dir })
+    }
+
+    /// Serve the route with the given status.
+    fn serve_route<RequestBody>(
+        &self,
+        req: &http::Request<RequestBody>,
+        route: &Route,
+        status: http::StatusCode,
+    ) -> Response {
+        let Representation { mut head, body } = route
+            .select(req.headers().get(http::header::ACCEPT_ENCODING))
+            .clone();
+
+        if head.status != status {
+            let parts = Arc::make_mut(&mut head);
+            parts.status = status;
+            if status != http::StatusCode::OK {
+                parts.headers.remove(http::header::ACCEPT_RANGES);
+            }
+        }
+
+        // Preconditions only apply to the successful responses.
+        if head.status.is_success() && conditional::is_not_modified(req.headers(), &head.headers) {
+            conditional::make_not_modified(Arc::make_mut(&mut head));
+            return Response {
+                head,
+                body: Body::None,
+            };
+        }
+
+        if head.status != http::StatusCode::OK {
+            return Response {
+                head,
+                body: Body::Full(body),
+            };
+        }
+
+        match range::evaluate(req.headers(), &head.headers, body.len()) {
+            range::Outcome::Full => Response {
+                head,
+                body: Body::Full(body),
+            },
+            range::Outcome::Partial(ranges) => {
+                range::make_partial(Arc::unwrap_or_clone(head), body, ranges).into()
+            }
+            range::Outcome::Unsatisfiable => {
+                range::make_unsatisfiable(Arc::unwrap_or_clone(head), body.len()).into()
+            }
+        }
+    }
+
+    /// Respond with the given error status, using the error page if there is one.
+    fn error_response<RequestBody>(
+        &self,
+        req: &http::Request<RequestBody>,
+        status: http::StatusCode,
+    ) -> Response {
+        let Some(route) = self.routes.error_page(status) else {
+            let mut res = http::Response::new(Body::Full(empty_bytes()));
+            *res.status_mut() = status;
+            return res.into();
+        };
+
+        let Representation { head, body } = route
+            .select(req.headers().get(http::header::ACCEPT_ENCODING))
+            .clone();
+        Response {
+            head,
+            body: Body::Full(body),
+        }
+    }
+
+    /// Handle an incoming request for a given path and provide the route to respond with.
+    ///
+    /// The request is considered to be a navigation, and the path is normalized like
+    /// the request paths.
+    pub fn handle_path(&self, path: &str) -> Option<&Route> {
+        self.routes.lookup(&path::normalize(path).ok()?)
+    }
+}
+
+/// Build a redirect response.
+fn redirect(status: http::StatusCode, location: http::HeaderValue) -> Response {
+    let mut res = http::Response::new(Body::Full(empty_bytes()));
+    *res.status_mut() = status;
+    res.headers_mut().insert(http::header::LOCATION, location);
+    res.into()
 }
 
 /// Returns empty bytes.
@@ -0,0 +1,132 @@
+//! The allowed HTTP methods.
+
+use std::str::FromStr;
+
+/// An error that can occur while configuring the allowed methods.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The method is not something the server knows how to handle.
+    #[error("method {0} is not supported, only GET, HEAD and OPTIONS are")]
+    Unsupported(http::Method),
+
+    /// The method is invalid.
+    #[error("invalid method: {0}")]
+    Invalid(http::method::InvalidMethod),
+
+    /// The `GET` method is required.
+    #[error("the GET method can not be disallowed")]
+    GetMissing,
+}
+
+/// The set of the HTTP methods the server responds to.
+///
+/// The `GET` is always allowed, the `HEAD` and `OPTIONS` can be turned off.
+#[derive(Debug, Clone)]
+pub struct AllowedMethods {
+    /// The allowed methods.
+    methods: Vec<http::Method>,
+
+    /// The precomputed `Allow` header value.
+    allow: http::HeaderValue,
+}
+
+impl AllowedMethods {
+    /// The methods the server is able to handle.
+    pub const SUPPORTED: [http::Method; 3] =
+        [http::Method::GET, http::Method::HEAD, http::Method::OPTIONS];
+
+    /// Create a new set of the allowed methods.
+    pub fn new(methods: impl IntoIterator<Item = http::Method>) -> Result<Self, Error> {
+        let mut allowed = Vec::with_capacity(Self::SUPPORTED.len());
+
+        for method in methods {
+            if !Self::SUPPORTED.contains(&method) {
+                return Err(Error::Unsupported(method));
+            }
+            if !allowed.contains(&method) {
+                allowed.push(method);
+            }
+        }
+
+        if !allowed.contains(&http::Method::GET) {
+            return Err(Error::GetMissing);
+        }
+
+        // Keep the canonical order regardless of the input.
+        allowed.sort_by_key(|method| {
+            Self::SUPPORTED
+                .iter()
+                .position(|supported| supported == method)
+        });
+
+        let allow = allowed
+            .iter()
+            .map(http::Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let allow = http::HeaderValue::try_from(allow).unwrap(); // method names are valid header values
+
+        Ok(Self {
+            methods: allowed,
+            allow,
+        })
+    }
+
+    /// Check whether the method is allowed.
+    pub fn contains(&self, method: &http::Method) -> bool {
+        self.methods.contains(method)
+    }
+
+    /// The `Allow` header value listing the allowed methods.
+    pub fn allow(&self) -> &http::HeaderValue {
+        &self.allow
+    }
+}
+
+impl Default for AllowedMethods {
+    fn default() -> Self {
+        Self::new(Self::SUPPORTED).unwrap() // supported methods are always valid
+    }
+}
+
+impl FromStr for AllowedMethods {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let methods = s
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| http::Method::from_str(&item.to_ascii_uppercase()).map_err(Error::Invalid))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(methods)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let cases: &[(&str, Option<&str>)] = &[
+            ("GET", Some("GET")),
+            ("get, head", Some("GET, HEAD")),
+            ("OPTIONS,GET,HEAD,GET", Some("GET, HEAD, OPTIONS")),
+            ("HEAD", None),
+            ("GET, POST", None),
+            ("", None),
+        ];
+
+        for (sample, expected) in cases {
+            let actual = AllowedMethods::from_str(sample).ok();
+            assert_eq!(
+                actual
+                    .as_ref()
+                    .map(|methods| methods.allow().to_str().unwrap()),
+                *expected,
+                "{sample}"
+            );
+        }
+    }
+}
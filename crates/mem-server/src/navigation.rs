@@ -0,0 +1,202 @@
+//! Telling the navigation requests apart from the asset requests.
+
+use std::str::FromStr;
+
+/// An error that can occur while parsing the asset prefixes.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The prefix does not start with a slash.
+    #[error("asset prefix {0:?} must start with a slash")]
+    PrefixStart(String),
+}
+
+/// The extensions that still denote the navigation paths.
+const NAVIGATION_EXTENSIONS: &[&str] = &["html", "htm"];
+
+/// The rules for telling the navigation requests, which get the fallback response when there
+/// is no matching route, apart from the asset requests, which get a real `404 Not Found`.
+///
+/// With the default rules every request is considered a navigation.
+#[derive(Debug, Default, Clone)]
+pub struct NavigationFilter {
+    /// Consider the paths with a file extension (like `/static/js/main.abc123.js`) the asset
+    /// paths.
+    ///
+    /// The `.html` and `.htm` extensions are still considered navigation.
+    pub assets_by_extension: bool,
+
+    /// The path prefixes (like `/static`) to consider the asset paths.
+    pub asset_prefixes: AssetPrefixes,
+
+    /// Only consider the requests that accept `text/html` navigation.
+    pub require_html_accept: bool,
+}
+
+impl NavigationFilter {
+    /// Check whether the request is a navigation request.
+    pub fn is_navigation(&self, path: &str, req_headers: &http::HeaderMap) -> bool {
+        if self.assets_by_extension && has_asset_extension(path) {
+            return false;
+        }
+
+        if self
+            .asset_prefixes
+            .0
+            .iter()
+            .any(|prefix| has_prefix(path, prefix))
+        {
+            return false;
+        }
+
+        if self.require_html_accept && !accepts_html(req_headers) {
+            return false;
+        }
+
+        true
+    }
+}
+
+/// The comma-separated list of the asset path prefixes, like `/static, /assets`.
+///
+/// The prefixes match on the path segments boundaries.
+#[derive(Debug, Default, Clone)]
+pub struct AssetPrefixes(pub Vec<String>);
+
+impl FromStr for AssetPrefixes {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|prefix| !prefix.is_empty())
+            .map(|prefix| {
+                if prefix.starts_with('/') {
+                    Ok(prefix.to_owned())
+                } else {
+                    Err(Error::PrefixStart(prefix.to_owned()))
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Check whether the last segment of the path has an extension that does not denote
+/// a navigation.
+fn has_asset_extension(path: &str) -> bool {
+    let last_segment = path.rsplit('/').next().unwrap_or_default();
+
+    match last_segment.rsplit_once('.') {
+        Some(("", _)) | None => false,
+        Some((_, ext)) => !NAVIGATION_EXTENSIONS
+            .iter()
+            .any(|navigation_ext| ext.eq_ignore_ascii_case(navigation_ext)),
+    }
+}
+
+/// Check whether the path is under the given prefix.
+fn has_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.strip_suffix('/').unwrap_or(prefix);
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Check whether the request `Accept` headers list the HTML.
+fn accepts_html(req_headers: &http::HeaderMap) -> bool {
+    req_headers
+        .get_all(http::header::ACCEPT)
+        .iter()
+        .flat_map(|val| val.as_bytes().split(|byte| *byte == b','))
+        .any(|item| {
+            let media_type = item
+                .split(|byte| *byte == b';')
+                .next()
+                .unwrap_or_default()
+                .trim_ascii();
+            media_type.eq_ignore_ascii_case(b"text/html")
+                || media_type.eq_ignore_ascii_case(b"application/xhtml+xml")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extensions() {
+        let cases: &[(&str, bool)] = &[
+            ("/", false),
+            ("/my/spa/route", false),
+            ("/static/js/main.abc123.js", true),
+            ("/styles.css", true),
+            ("/page.html", false),
+            ("/page.HTM", false),
+            ("/.well-known", false),
+            ("/v1.2/route", false),
+        ];
+
+        for (path, expected) in cases {
+            assert_eq!(has_asset_extension(path), *expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn prefixes() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("/static/js/main.js", "/static", true),
+            ("/static/js/main.js", "/static/", true),
+            ("/static", "/static/", true),
+            ("/statics/a", "/static", false),
+            ("/app/static", "/static", false),
+        ];
+
+        for (path, prefix, expected) in cases {
+            assert_eq!(has_prefix(path, prefix), *expected, "{path} {prefix}");
+        }
+    }
+
+    #[test]
+    fn prefixes_parsing() {
+        let cases: &[(&str, Option<&[&str]>)] = &[
+            ("", Some(&[])),
+            ("/static", Some(&["/static"])),
+            (" /static/ , /assets,", Some(&["/static/", "/assets"])),
+            ("static", None),
+            ("/static, assets", None),
+        ];
+
+        for (s, expected) in cases {
+            assert_eq!(
+                AssetPrefixes::from_str(s).ok().map(|prefixes| prefixes.0),
+                expected.map(|prefixes| prefixes.iter().map(|p| p.to_string()).collect()),
+                "{s:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn accept() {
+        let cases: &[(&str, bool)] = &[
+            (
+                "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+                true,
+            ),
+            ("application/xhtml+xml", true),
+            ("*/*", false),
+            ("application/javascript, */*;q=0.1", false),
+        ];
+
+        for (accept, expected) in cases {
+            let mut req_headers = http::HeaderMap::new();
+            req_headers.insert(
+                http::header::ACCEPT,
+                http::HeaderValue::from_str(accept).unwrap(),
+            );
+            assert_eq!(accepts_html(&req_headers), *expected, "{accept}");
+        }
+
+        assert!(!accepts_html(&http::HeaderMap::new()));
+    }
+}
@@ -0,0 +1,181 @@
+//! The request path normalization.
+//!
+//! The routes are keyed by the decoded paths, so the request paths are percent-decoded and
+//! have their dot-segments removed before the lookup.
+
+use std::borrow::Cow;
+
+/// An error that can occur while normalizing the path.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// The path has an encoded slash, which can not be told apart from the segment separator
+    /// once decoded.
+    #[error("the path has an encoded slash")]
+    EncodedSlash,
+
+    /// The path has an encoded NUL byte.
+    #[error("the path has an encoded NUL byte")]
+    Nul,
+
+    /// The decoded path is not valid UTF-8.
+    #[error("the decoded path is not valid UTF-8")]
+    NonUtf8,
+}
+
+/// Percent-decode the path and remove the dot-segments and the empty segments from it.
+///
+/// The trailing slash is kept. The malformed percent-encoded sequences are kept as-is.
+pub fn normalize(path: &str) -> Result<Cow<'_, str>, Error> {
+    if !path.starts_with('/') {
+        return Ok(Cow::Borrowed(path));
+    }
+
+    if !path.contains('%') && !path.contains("//") && !has_dot_segments(path) {
+        return Ok(Cow::Borrowed(path));
+    }
+
+    let decoded = decode(path)?;
+
+    let mut segments: Vec<&str> = Vec::new();
+    let mut trailing_slash = false;
+
+    for segment in decoded.split('/').skip(1) {
+        trailing_slash = matches!(segment, "" | "." | "..");
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let mut normalized = String::with_capacity(decoded.len());
+    for segment in &segments {
+        normalized.push('/');
+        normalized.push_str(segment);
+    }
+    if trailing_slash || segments.is_empty() {
+        normalized.push('/');
+    }
+
+    Ok(Cow::Owned(normalized))
+}
+
+/// Percent-encode the decoded path for use in the `Location` header.
+pub fn encode(path: &str) -> Cow<'_, str> {
+    if path.bytes().all(is_path_byte) {
+        return Cow::Borrowed(path);
+    }
+
+    let mut encoded = String::with_capacity(path.len() * 3);
+    for byte in path.bytes() {
+        if is_path_byte(byte) {
+            encoded.push(byte.into());
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    Cow::Owned(encoded)
+}
+
+/// Check whether the byte can be in the path as-is.
+fn is_path_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@/".contains(&byte)
+}
+
+/// Check whether the path has any `.` or `..` segments.
+fn has_dot_segments(path: &str) -> bool {
+    path.split('/').any(|segment| matches!(segment, "." | ".."))
+}
+
+/// Percent-decode the path.
+fn decode(path: &str) -> Result<String, Error> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let byte = bytes[idx];
+
+        let hex = (byte == b'%')
+            .then(|| bytes.get(idx + 1..idx + 3))
+            .flatten()
+            // The radix parsing alone would accept the sign, like in `%+f`.
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match hex {
+            Some(b'/') => return Err(Error::EncodedSlash),
+            Some(0) => return Err(Error::Nul),
+            Some(decoded_byte) => {
+                decoded.push(decoded_byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(byte);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| Error::NonUtf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalization() {
+        let cases: &[(&str, Result<&str, Error>)] = &[
+            ("/", Ok("/")),
+            ("/index.html", Ok("/index.html")),
+            ("/nested/", Ok("/nested/")),
+            ("/my%20file.png", Ok("/my file.png")),
+            ("/%C3%B1.svg", Ok("/ñ.svg")),
+            ("/%c3%b1.svg", Ok("/ñ.svg")),
+            ("/100%.txt", Ok("/100%.txt")),
+            ("/100%25.txt", Ok("/100%.txt")),
+            ("/%+f", Ok("/%+f")),
+            ("/a%-1b", Ok("/a%-1b")),
+            ("/%zz", Ok("/%zz")),
+            ("/a/../index.html", Ok("/index.html")),
+            ("//index.html", Ok("/index.html")),
+            ("/./a//b/", Ok("/a/b/")),
+            ("/a/%2e%2E/b", Ok("/b")),
+            ("/../../etc/passwd", Ok("/etc/passwd")),
+            ("/a/..", Ok("/")),
+            ("/a/b/..", Ok("/a/")),
+            ("/a%2Fb", Err(Error::EncodedSlash)),
+            ("/a%2fb", Err(Error::EncodedSlash)),
+            ("/a%00b", Err(Error::Nul)),
+            ("/%ff", Err(Error::NonUtf8)),
+            ("*", Ok("*")),
+        ];
+
+        for (path, expected) in cases {
+            assert_eq!(
+                normalize(path).as_deref().map_err(Clone::clone),
+                expected.as_deref().map_err(Clone::clone),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn encoding() {
+        let cases: &[(&str, &str)] = &[
+            ("/a/b.html", "/a/b.html"),
+            ("/my file.png", "/my%20file.png"),
+            ("/ñ.svg", "/%C3%B1.svg"),
+            ("/100%.txt", "/100%25.txt"),
+            ("/a?b#c", "/a%3Fb%23c"),
+        ];
+
+        for (path, expected) in cases {
+            assert_eq!(encode(path), *expected, "{path}");
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Range requests handling.
+//!
+//! See <https://www.rfc-editor.org/rfc/rfc9110#name-range-requests>.
+
+use std::ops::Range;
+
+use bytes::Bytes;
+use http::header::{CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+
+use crate::Body;
+
+/// The max amount of ranges in a single request we are willing to process.
+///
+/// Requests with more ranges than that get the full content instead.
+const MAX_RANGES: usize = 64;
+
+/// The boundary to use for multipart responses when the entity tag is not available.
+const FALLBACK_BOUNDARY: &str = "sap-byteranges-7d1c9b04f3e2a6d5";
+
+/// The outcome of the range request evaluation.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The full content should be sent.
+    Full,
+
+    /// Only the given ranges of the content should be sent.
+    ///
+    /// The ranges are ordered, non-overlapping and non-empty.
+    Partial(Vec<Range<usize>>),
+
+    /// None of the requested ranges can be satisfied.
+    Unsatisfiable,
+}
+
+/// Evaluate the range request with the given headers against the response with the given headers
+/// and the content length.
+pub fn evaluate(
+    req_headers: &http::HeaderMap,
+    res_headers: &http::HeaderMap,
+    len: usize,
+) -> Outcome {
+    let Some(range) = req_headers.get(RANGE) else {
+        return Outcome::Full;
+    };
+
+    if let Some(if_range) = req_headers.get(IF_RANGE) {
+        if !if_range_matches(if_range, res_headers) {
+            return Outcome::Full;
+        }
+    }
+
+    let Some(specs) = parse_range(range.as_bytes()) else {
+        // Unparseable or unsupported ranges are ignored.
+        return Outcome::Full;
+    };
+
+    let mut ranges: Vec<Range<usize>> = specs
+        .into_iter()
+        .filter_map(|spec| spec.resolve(len))
+        .collect();
+
+    if ranges.is_empty() {
+        return Outcome::Unsatisfiable;
+    }
+
+    ranges.sort_by_key(|range| range.start);
+
+    let mut coalesced: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match coalesced.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => coalesced.push(range),
+        }
+    }
+
+    Outcome::Partial(coalesced)
+}
+
+/// Turn the response into the partial content response with the given ranges.
+pub fn make_partial(
+    mut parts: http::response::Parts,
+    body: Bytes,
+    ranges: Vec<Range<usize>>,
+) -> http::Response<Body> {
+    let len = body.len();
+
+    parts.status = http::StatusCode::PARTIAL_CONTENT;
+
+    if let [range] = ranges.as_slice() {
+        parts
+            .headers
+            .insert(CONTENT_RANGE, content_range(range, len));
+        return http::Response::from_parts(parts, Body::Full(body.slice(range.clone())));
+    }
+
+    let boundary = boundary(&parts.headers);
+    let content_type = parts.headers.remove(CONTENT_TYPE);
+
+    let mut chunks = Vec::with_capacity(ranges.len() * 2 + 1);
+    for range in ranges {
+        let mut part_headers = format!("\r\n--{boundary}\r\n");
+        if let Some(content_type) = &content_type {
+            part_headers.push_str("content-type: ");
+            part_headers.push_str(content_type.to_str().unwrap_or_default());
+            part_headers.push_str("\r\n");
+        }
+        part_headers.push_str("content-range: ");
+        part_headers.push_str(content_range(&range, len).to_str().unwrap());
+        part_headers.push_str("\r\n\r\n");
+
+        chunks.push(Bytes::from(part_headers));
+        chunks.push(body.slice(range));
+    }
+    chunks.push(Bytes::from(format!("\r\n--{boundary}--\r\n")));
+
+    parts.headers.insert(
+        CONTENT_TYPE,
+        http::HeaderValue::try_from(format!("multipart/byteranges; boundary={boundary}")).unwrap(), // boundary is always a valid header value
+    );
+
+    http::Response::from_parts(parts, Body::Chunks(chunks))
+}
+
+/// Turn the response into the range not satisfiable response.
+pub fn make_unsatisfiable(mut parts: http::response::Parts, len: usize) -> http::Response<Body> {
+    parts.status = http::StatusCode::RANGE_NOT_SATISFIABLE;
+    parts.headers.remove(CONTENT_TYPE);
+    parts.headers.insert(
+        CONTENT_RANGE,
+        http::HeaderValue::try_from(format!("bytes */{len}")).unwrap(), // digits are always valid
+    );
+    http::Response::from_parts(parts, Body::Full(Bytes::new()))
+}
+
+/// A single range spec from the `Range` header.
+#[derive(Debug, PartialEq, Eq)]
+enum Spec {
+    /// The `first-last` or `first-` form.
+    FromTo(usize, Option<usize>),
+
+    /// The `-suffix` form.
+    Suffix(usize),
+}
+
+impl Spec {
+    /// Resolve the spec against the content length, producing the range if it is satisfiable.
+    fn resolve(&self, len: usize) -> Option<Range<usize>> {
+        match *self {
+            Self::FromTo(first, last) => {
+                if first >= len {
+                    return None;
+                }
+                let end = last.map_or(len, |last| last.saturating_add(1).min(len));
+                Some(first..end)
+            }
+            Self::Suffix(0) => None,
+            Self::Suffix(suffix) => Some(len.saturating_sub(suffix)..len),
+        }
+        .filter(|range| !range.is_empty())
+    }
+}
+
+/// Parse the `Range` header value.
+///
+/// Returns [`None`] if the header is invalid or uses a unit other than `bytes`.
+fn parse_range(range: &[u8]) -> Option<Vec<Spec>> {
+    let range = std::str::from_utf8(range).ok()?;
+    let (unit, specs) = range.split_once('=')?;
+
+    if !unit.trim().eq_ignore_ascii_case("bytes") {
+        return None;
+    }
+
+    let specs = specs
+        .split(',')
+        .map(str::trim)
+        .filter(|spec| !spec.is_empty())
+        .map(|spec| {
+            let (first, last) = spec.split_once('-')?;
+            let (first, last) = (first.trim(), last.trim());
+
+            if first.is_empty() {
+                return Some(Spec::Suffix(parse_number(last)?));
+            }
+
+            let first = parse_number(first)?;
+            let last = if last.is_empty() {
+                None
+            } else {
+                Some(parse_number(last)?)
+            };
+
+            if last.is_some_and(|last| last < first) {
+                return None;
+            }
+
+            Some(Spec::FromTo(first, last))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    if specs.is_empty() || specs.len() > MAX_RANGES {
+        return None;
+    }
+
+    Some(specs)
+}
+
+/// Parse a decimal number strictly.
+fn parse_number(val: &str) -> Option<usize> {
+    if val.is_empty() || !val.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    val.parse().ok()
+}
+
+/// Evaluate the `If-Range` precondition.
+///
+/// Entity tags are compared with the strong comparison, and dates must match exactly.
+fn if_range_matches(if_range: &http::HeaderValue, res_headers: &http::HeaderMap) -> bool {
+    let if_range = if_range.as_bytes().trim_ascii();
+
+    if if_range.starts_with(b"\"") || if_range.starts_with(b"W/") {
+        return res_headers
+            .get(ETAG)
+            .is_some_and(|etag| !if_range.starts_with(b"W/") && etag.as_bytes() == if_range);
+    }
+
+    res_headers
+        .get(LAST_MODIFIED)
+        .is_some_and(|last_modified| last_modified.as_bytes() == if_range)
+}
+
+/// Format the `Content-Range` header value.
+fn content_range(range: &Range<usize>, len: usize) -> http::HeaderValue {
+    let last = range.end - 1;
+    let value = format!("bytes {}-{last}/{len}", range.start);
+    http::HeaderValue::try_from(value).unwrap() // digits are always valid
+}
+
+/// Pick the multipart boundary for the response.
+///
+/// The boundary is derived from the entity tag when possible, as the content is unlikely to
+/// include its own hash.
+fn boundary(res_headers: &http::HeaderMap) -> String {
+    res_headers
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(|etag| etag.trim_start_matches("W/").trim_matches('"'))
+        .filter(|etag| {
+            !etag.is_empty()
+                && etag
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+        })
+        .map_or_else(
+            || FALLBACK_BOUNDARY.to_owned(),
+            |etag| format!("sap-byteranges-{etag}"),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate_range(range: &str, len: usize) -> Outcome {
+        let mut req_headers = http::HeaderMap::new();
+        req_headers.insert(RANGE, http::HeaderValue::from_str(range).unwrap());
+        evaluate(&req_headers, &http::HeaderMap::new(), len)
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn ranges() {
+        let cases: &[(&str, Outcome)] = &[
+            ("bytes=0-0", Outcome::Partial(vec![0..1])),
+            ("bytes=0-9", Outcome::Partial(vec![0..10])),
+            ("bytes=0-999", Outcome::Partial(vec![0..100])),
+            ("bytes=90-", Outcome::Partial(vec![90..100])),
+            ("bytes=-10", Outcome::Partial(vec![90..100])),
+            ("bytes=-1000", Outcome::Partial(vec![0..100])),
+            ("bytes=0-1, 5-6", Outcome::Partial(vec![0..2, 5..7])),
+            ("bytes=5-6,0-1", Outcome::Partial(vec![0..2, 5..7])),
+            ("bytes=0-5, 3-8", Outcome::Partial(vec![0..9])),
+            ("bytes=100-", Outcome::Unsatisfiable),
+            ("bytes=-0", Outcome::Unsatisfiable),
+            ("bytes=100-200, 300-", Outcome::Unsatisfiable),
+            ("bytes=100-200, 0-0", Outcome::Partial(vec![0..1])),
+            ("bytes=5-1", Outcome::Full),
+            ("bytes=a-b", Outcome::Full),
+            ("bytes=", Outcome::Full),
+            ("items=0-1", Outcome::Full),
+            ("0-1", Outcome::Full),
+        ];
+
+        for (range, expected) in cases {
+            assert_eq!(&evaluate_range(range, 100), expected, "{range}");
+        }
+    }
+
+    #[test]
+    fn if_range() {
+        let mut res_headers = http::HeaderMap::new();
+        res_headers.insert(ETAG, http::HeaderValue::from_static(r#""abc""#));
+        res_headers.insert(
+            LAST_MODIFIED,
+            http::HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+
+        let cases: &[(&str, bool)] = &[
+            (r#""abc""#, true),
+            (r#""def""#, false),
+            (r#"W/"abc""#, false),
+            ("Wed, 21 Oct 2015 07:28:00 GMT", true),
+            ("Thu, 22 Oct 2015 07:28:00 GMT", false),
+        ];
+
+        for (if_range, expected) in cases {
+            assert_eq!(
+                if_range_matches(
+                    &http::HeaderValue::from_str(if_range).unwrap(),
+                    &res_headers
+                ),
+                *expected,
+                "{if_range}"
+            );
+        }
+    }
+}
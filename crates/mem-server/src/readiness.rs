@@ -0,0 +1,55 @@
+//! The readiness to serve the traffic.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Whether the server is ready to serve the traffic.
+///
+/// The server is ready once the site is loaded, until the shutdown starts. A failed reload
+/// keeps the previous site in service, so it does not affect the readiness.
+/// The clones share the state, so it can be updated from the outside of the server.
+#[derive(Debug, Clone, Default)]
+pub struct Readiness(Arc<State>);
+
+/// The shared readiness state.
+#[derive(Debug, Default)]
+struct State {
+    /// Whether a site is loaded and in service.
+    loaded: AtomicBool,
+
+    /// Whether the latest reloading of the site failed, leaving the previous one in service.
+    reload_failed: AtomicBool,
+
+    /// Whether the shutdown started.
+    stopping: AtomicBool,
+}
+
+impl Readiness {
+    /// Record whether a site is loaded and in service.
+    pub fn set_loaded(&self, loaded: bool) {
+        self.0.loaded.store(loaded, Ordering::Relaxed);
+    }
+
+    /// Record whether the latest reloading of the site failed.
+    pub fn set_reload_failed(&self, reload_failed: bool) {
+        self.0.reload_failed.store(reload_failed, Ordering::Relaxed);
+    }
+
+    /// Whether the latest reloading of the site failed, which is reported by the readiness
+    /// endpoint without affecting the readiness.
+    pub fn is_reload_failed(&self) -> bool {
+        self.0.reload_failed.load(Ordering::Relaxed)
+    }
+
+    /// Record that the shutdown started, which is final.
+    pub fn set_stopping(&self) {
+        self.0.stopping.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the server is ready.
+    pub fn is_ready(&self) -> bool {
+        self.0.loaded.load(Ordering::Relaxed) && !self.0.stopping.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,433 @@
+//! Redirect and rewrite rules.
+
+use std::str::FromStr;
+
+/// An error that can occur while building a rule.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The pattern does not start with a slash.
+    #[error("pattern {0:?} must start with a slash")]
+    PatternStart(String),
+
+    /// The splat is not the last segment of the pattern.
+    #[error("pattern {0:?} can only have the splat as the last segment")]
+    PatternSplat(String),
+
+    /// The placeholder has no name.
+    #[error("pattern {0:?} has a placeholder with no name")]
+    PatternPlaceholder(String),
+
+    /// The rewrite target is not a local path.
+    #[error("the rewrite target {0:?} must be a local path")]
+    RewriteTarget(String),
+
+    /// The redirect target is not a valid header value.
+    #[error("the redirect target {0:?} is invalid")]
+    RedirectTarget(String),
+
+    /// The status is neither a redirect, nor a success or an error.
+    ///
+    /// Only the `301`, `302`, `303`, `307` and `308` are the redirects, as the other `3xx`
+    /// statuses do not redirect to the `Location`.
+    #[error("status {0} can not be used for a rule")]
+    Status(http::StatusCode),
+}
+
+/// The ordered list of the redirect and rewrite rules.
+#[derive(Debug, Default, Clone)]
+pub struct Redirects(pub Vec<Rule>);
+
+impl Redirects {
+    /// Find the action for the request with the given path and query.
+    ///
+    /// The first matching rule wins. Unless forced, the rules do not apply to the paths
+    /// that are shadowed by an existing route.
+    pub fn evaluate(&self, path: &str, query: Option<&str>, shadowed: bool) -> Option<Action> {
+        self.0
+            .iter()
+            .filter(|rule| rule.force || !shadowed)
+            .find_map(|rule| rule.evaluate(path, query))
+    }
+}
+
+/// A redirect or rewrite rule.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// The pattern for the paths to apply the rule to.
+    pub from: Pattern,
+
+    /// The target, with the `:name` placeholders and the `:splat` to be substituted.
+    pub to: String,
+
+    /// The status to respond with.
+    ///
+    /// The redirect statuses redirect to the target, and the others serve the target route
+    /// content with the given status.
+    pub status: http::StatusCode,
+
+    /// Whether to apply the rule even when there is an exact route at the path.
+    pub force: bool,
+
+    /// Whether to pass the request query on to the redirect target when the target has
+    /// no query of its own.
+    pub preserve_query: bool,
+}
+
+impl Rule {
+    /// Create a new rule, validating the target against the status.
+    pub fn new(
+        from: Pattern,
+        to: String,
+        status: http::StatusCode,
+        force: bool,
+    ) -> Result<Self, Error> {
+        if !is_redirect(status)
+            && !status.is_success()
+            && !status.is_client_error()
+            && !status.is_server_error()
+        {
+            return Err(Error::Status(status));
+        }
+
+        if is_redirect(status) {
+            if http::HeaderValue::from_str(&to).is_err() {
+                return Err(Error::RedirectTarget(to));
+            }
+        } else if !to.starts_with('/') {
+            return Err(Error::RewriteTarget(to));
+        }
+
+        Ok(Self {
+            from,
+            to,
+            status,
+            force,
+            preserve_query: true,
+        })
+    }
+
+    /// Move the rule under the given path prefix, like `/admin`, prefixing both the pattern
+    /// and the local target.
+    pub fn mount(&mut self, prefix: &str) {
+        let prefix = prefix.trim_end_matches('/');
+        if prefix.is_empty() {
+            return;
+        }
+
+        let literals = prefix
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| Segment::Literal(segment.to_owned()));
+        self.from.segments.splice(0..0, literals);
+
+        if self.to.starts_with('/') {
+            self.to = format!("{prefix}{}", self.to);
+        }
+    }
+
+    /// Check whether the rule is a rewrite, as opposed to a redirect.
+    pub fn is_rewrite(&self) -> bool {
+        !is_redirect(self.status)
+    }
+
+    /// Evaluate the rule against the request path and query.
+    pub fn evaluate(&self, path: &str, query: Option<&str>) -> Option<Action> {
+        let captures = self.from.captures(path)?;
+        // The path is decoded, so the captured values are encoded back to fit into the target.
+        let captures: Vec<_> = captures
+            .into_iter()
+            .map(|(name, value)| (name, crate::path::encode(value)))
+            .collect();
+        let target = substitute(&self.to, &captures);
+
+        if self.is_rewrite() {
+            return Some(Action::Rewrite(self.status, target));
+        }
+
+        let location = match query {
+            Some(query) if self.preserve_query && !query.is_empty() && !target.contains('?') => {
+                format!("{target}?{query}")
+            }
+            _ => target,
+        };
+
+        // The substituted values are encoded, so the location is a valid header value.
+        let location = http::HeaderValue::try_from(location).ok()?;
+
+        Some(Action::Redirect(self.status, location))
+    }
+}
+
+/// The action to take for the request.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Redirect to the given location.
+    Redirect(http::StatusCode, http::HeaderValue),
+
+    /// Serve the route at the given path with the given status.
+    Rewrite(http::StatusCode, String),
+}
+
+/// A path pattern.
+///
+/// The segments starting with a colon, like `/:year`, are the placeholders matching any single
+/// segment, and the `*` as the last segment is the splat matching the rest of the path.
+/// The trailing slashes are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    /// The pattern segments, except for the splat.
+    segments: Vec<Segment>,
+
+    /// Whether the pattern ends with the splat.
+    splat: bool,
+}
+
+/// A path pattern segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// A segment that must match exactly.
+    Literal(String),
+
+    /// A segment that matches anything, captured by the name.
+    Placeholder(String),
+}
+
+impl FromStr for Pattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(rest) = s.strip_prefix('/') else {
+            return Err(Error::PatternStart(s.to_owned()));
+        };
+
+        let mut segments = Vec::new();
+        let mut splat = false;
+
+        for segment in rest.split('/').filter(|segment| !segment.is_empty()) {
+            if splat {
+                return Err(Error::PatternSplat(s.to_owned()));
+            }
+
+            if segment == "*" {
+                splat = true;
+                continue;
+            }
+
+            if let Some(name) = segment.strip_prefix(':') {
+                if name.is_empty() {
+                    return Err(Error::PatternPlaceholder(s.to_owned()));
+                }
+                segments.push(Segment::Placeholder(name.to_owned()));
+                continue;
+            }
+
+            segments.push(Segment::Literal(segment.to_owned()));
+        }
+
+        Ok(Self { segments, splat })
+    }
+}
+
+impl Pattern {
+    /// Match the path against the pattern, capturing the placeholders and the splat.
+    pub fn captures<'a>(&'a self, path: &'a str) -> Option<Vec<(&'a str, &'a str)>> {
+        let mut rest = path.strip_prefix('/')?;
+        let mut captures = Vec::new();
+
+        for segment in &self.segments {
+            let (value, next) = rest.split_once('/').unwrap_or((rest, ""));
+            if value.is_empty() {
+                return None;
+            }
+
+            match segment {
+                Segment::Literal(literal) if literal == value => {}
+                Segment::Literal(_) => return None,
+                Segment::Placeholder(name) => captures.push((name.as_str(), value)),
+            }
+
+            rest = next;
+        }
+
+        if self.splat {
+            captures.push(("splat", rest));
+            return Some(captures);
+        }
+
+        rest.is_empty().then_some(captures)
+    }
+}
+
+/// Substitute the `:name` placeholders in the target with the captured values.
+///
+/// The placeholders with no captured value are kept as-is.
+fn substitute<Value: AsRef<str>>(target: &str, captures: &[(&str, Value)]) -> String {
+    if captures.is_empty() {
+        return target.to_owned();
+    }
+
+    let mut result = String::with_capacity(target.len());
+    let mut rest = target;
+
+    while let Some(idx) = rest.find(':') {
+        result.push_str(&rest[..idx]);
+        rest = &rest[idx + 1..];
+
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        let name = &rest[..name_len];
+
+        match captures.iter().find(|(captured, _)| *captured == name) {
+            Some((_, value)) => result.push_str(value.as_ref()),
+            None => {
+                result.push(':');
+                result.push_str(name);
+            }
+        }
+
+        rest = &rest[name_len..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Check whether the status redirects to the `Location`.
+pub fn is_redirect(status: http::StatusCode) -> bool {
+    matches!(status.as_u16(), 301 | 302 | 303 | 307 | 308)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(from: &str, to: &str, status: u16, force: bool) -> Rule {
+        Rule::new(
+            from.parse().unwrap(),
+            to.into(),
+            http::StatusCode::from_u16(status).unwrap(),
+            force,
+        )
+        .unwrap()
+    }
+
+    fn redirect(status: u16, location: &'static str) -> Option<Action> {
+        Some(Action::Redirect(
+            http::StatusCode::from_u16(status).unwrap(),
+            http::HeaderValue::from_static(location),
+        ))
+    }
+
+    fn rewrite(status: u16, path: &str) -> Option<Action> {
+        Some(Action::Rewrite(
+            http::StatusCode::from_u16(status).unwrap(),
+            path.into(),
+        ))
+    }
+
+    #[test]
+    fn evaluation() {
+        let redirects = Redirects(vec![
+            rule("/home", "/", 301, false),
+            rule("/blog/*", "/news/:splat", 302, false),
+            rule("/news/:year/:month", "/archive/:year-:month", 200, false),
+            rule("/old", "https://example.com:8443/new", 301, false),
+            rule("/shadowed", "/other", 301, false),
+            rule("/forced", "/other", 301, true),
+            rule("/app/*", "/app.html", 200, false),
+            rule("/gone", "/404.html", 404, false),
+        ]);
+
+        let shadowed = ["/shadowed", "/forced"];
+
+        let cases: &[(&str, Option<&str>, Option<Action>)] = &[
+            ("/home", None, redirect(301, "/")),
+            ("/home/", None, redirect(301, "/")),
+            ("/home", Some("a=1"), redirect(301, "/?a=1")),
+            ("/homes", None, None),
+            ("/blog/2024/post", None, redirect(302, "/news/2024/post")),
+            ("/blog", None, redirect(302, "/news/")),
+            ("/news/2024/05", None, rewrite(200, "/archive/2024-05")),
+            ("/news/2024", None, None),
+            ("/news/2024/05/01", None, None),
+            ("/old", None, redirect(301, "https://example.com:8443/new")),
+            ("/shadowed", None, None),
+            ("/forced", None, redirect(301, "/other")),
+            ("/app/settings/profile", None, rewrite(200, "/app.html")),
+            ("/gone", None, rewrite(404, "/404.html")),
+            ("/blog/my post", None, redirect(302, "/news/my%20post")),
+            ("/news/ñ/05", None, rewrite(200, "/archive/%C3%B1-05")),
+        ];
+
+        let mut dropping = rule("/drop", "/dropped", 302, false);
+        dropping.preserve_query = false;
+        assert_eq!(
+            dropping.evaluate("/drop", Some("a=1")),
+            redirect(302, "/dropped")
+        );
+
+        for (path, query, expected) in cases {
+            assert_eq!(
+                &redirects.evaluate(path, *query, shadowed.contains(path)),
+                expected,
+                "{path} {query:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn mounting() {
+        let mut mounted = rule("/blog/*", "/news/:splat", 302, false);
+        mounted.mount("/admin/");
+        assert_eq!(
+            mounted.evaluate("/admin/blog/a", None),
+            redirect(302, "/admin/news/a")
+        );
+        assert_eq!(mounted.evaluate("/blog/a", None), None);
+
+        let mut external = rule("/docs", "https://example.com/", 301, false);
+        external.mount("/admin");
+        assert_eq!(
+            external.evaluate("/admin/docs", None),
+            redirect(301, "https://example.com/")
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Pattern::from_str("home").is_err());
+        assert!(Pattern::from_str("/a/*/b").is_err());
+        assert!(Pattern::from_str("/a/:").is_err());
+
+        let pattern = Pattern::from_str("/a").unwrap();
+        assert!(Rule::new(
+            pattern.clone(),
+            "https://example.com".into(),
+            http::StatusCode::OK,
+            false
+        )
+        .is_err());
+        assert!(Rule::new(
+            pattern.clone(),
+            "/b".into(),
+            http::StatusCode::CONTINUE,
+            false
+        )
+        .is_err());
+        for status in [300, 304, 305, 306] {
+            let status = http::StatusCode::from_u16(status).unwrap();
+            assert!(
+                Rule::new(pattern.clone(), "/b".into(), status, false).is_err(),
+                "{status}"
+            );
+        }
+        for status in [301, 302, 303, 307, 308] {
+            let status = http::StatusCode::from_u16(status).unwrap();
+            assert!(
+                Rule::new(pattern.clone(), "/b".into(), status, false).is_ok(),
+                "{status}"
+            );
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! The table of the routes to serve.
+
+use std::collections::HashMap;
+
+use crate::Route;
+
+/// The table of the routes to serve.
+///
+/// The lookups are done via the hash map access rather than by iterating over the routes:
+/// the exact routes are looked up once, and the prefix routes are looked up once per path
+/// segment.
+#[derive(Debug, Default, Clone)]
+pub struct RouteTable {
+    /// The routes matching the path exactly.
+    exact: HashMap<String, Route>,
+
+    /// The routes matching the path prefix, keyed by the prefix without the trailing slash.
+    prefixes: HashMap<String, Route>,
+
+    /// The route to use when nothing else matches.
+    fallback: Option<Route>,
+
+    /// The routes to use for the error responses, keyed by the status.
+    error_pages: HashMap<http::StatusCode, Route>,
+}
+
+impl RouteTable {
+    /// Find the route to respond with for the given path.
+    ///
+    /// The exact routes take precedence over the prefix routes, with the longest prefix winning,
+    /// and the fallback is used when nothing else matches.
+    pub fn lookup(&self, path: &str) -> Option<&Route> {
+        if let Some(route) = self.exact.get(path) {
+            return Some(route);
+        }
+
+        self.lookup_fallback(path)
+    }
+
+    /// Find the route to respond with for the given path when there is no exact route.
+    ///
+    /// The longest matching prefix route wins, and the fallback is used when there are none.
+    pub fn lookup_fallback(&self, path: &str) -> Option<&Route> {
+        if let Some(route) = self.lookup_prefix(path) {
+            return Some(route);
+        }
+
+        self.fallback.as_ref()
+    }
+
+    /// Find the route with the longest prefix matching the given path.
+    ///
+    /// Prefixes only match on the path segments boundaries, so the `/app` prefix matches
+    /// the `/app` and `/app/page`, but not the `/application`.
+    pub fn lookup_prefix(&self, path: &str) -> Option<&Route> {
+        if self.prefixes.is_empty() {
+            return None;
+        }
+
+        let mut candidate = path.strip_suffix('/').unwrap_or(path);
+        loop {
+            if let Some(route) = self.prefixes.get(candidate) {
+                return Some(route);
+            }
+
+            let idx = candidate.rfind('/')?;
+            candidate = &candidate[..idx];
+        }
+    }
+
+    /// Get the route matching the path exactly.
+    pub fn get(&self, path: &str) -> Option<&Route> {
+        self.exact.get(path)
+    }
+
+    /// Get the route matching the path exactly for modification.
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut Route> {
+        self.exact.get_mut(path)
+    }
+
+    /// Check whether there is a route matching the path exactly.
+    pub fn contains(&self, path: &str) -> bool {
+        self.exact.contains_key(path)
+    }
+
+    /// Add a route matching the path exactly.
+    ///
+    /// Returns the route previously registered at this path, if any.
+    pub fn insert(&mut self, path: String, mut route: Route) -> Option<Route> {
+        route.prepare();
+        self.exact.insert(path, route)
+    }
+
+    /// Remove the route matching the path exactly.
+    pub fn remove(&mut self, path: &str) -> Option<Route> {
+        self.exact.remove(path)
+    }
+
+    /// Add a route matching the path prefix.
+    ///
+    /// Returns the route previously registered at this prefix, if any.
+    pub fn insert_prefix(&mut self, prefix: &str, mut route: Route) -> Option<Route> {
+        route.prepare();
+        let prefix = prefix.strip_suffix('/').unwrap_or(prefix);
+        self.prefixes.insert(prefix.to_owned(), route)
+    }
+
+    /// Get the route matching the path prefix exactly.
+    pub fn get_prefix(&self, prefix: &str) -> Option<&Route> {
+        let prefix = prefix.strip_suffix('/').unwrap_or(prefix);
+        self.prefixes.get(prefix)
+    }
+
+    /// Set the route to use when nothing else matches.
+    pub fn set_fallback(&mut self, route: Option<Route>) {
+        self.fallback = route.map(|mut route| {
+            route.prepare();
+            route
+        });
+    }
+
+    /// The route to use when nothing else matches.
+    pub fn fallback(&self) -> Option<&Route> {
+        self.fallback.as_ref()
+    }
+
+    /// Set the route to respond with for the given error status.
+    ///
+    /// Returns the route previously set for this status, if any.
+    pub fn set_error_page(&mut self, status: http::StatusCode, mut route: Route) -> Option<Route> {
+        route.prepare();
+        self.error_pages.insert(status, route)
+    }
+
+    /// The route to respond with for the given error status.
+    pub fn error_page(&self, status: http::StatusCode) -> Option<&Route> {
+        self.error_pages.get(&status)
+    }
+
+    /// Iterate over the exact routes paths.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.exact.keys().map(String::as_str)
+    }
+
+    /// Iterate over the exact routes for modification.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut Route)> {
+        self.exact
+            .iter_mut()
+            .map(|(path, route)| (path.as_str(), route))
+    }
+
+    /// The amount of the exact routes.
+    pub fn len(&self) -> usize {
+        self.exact.len()
+    }
+
+    /// Whether there are no exact routes.
+    pub fn is_empty(&self) -> bool {
+        self.exact.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(name: &'static str) -> Route {
+        http::Response::new(bytes::Bytes::from_static(name.as_bytes())).into()
+    }
+
+    fn lookup<'a>(table: &'a RouteTable, path: &str) -> Option<&'a [u8]> {
+        table
+            .lookup(path)
+            .map(|route| route.identity.body().as_ref())
+    }
+
+    #[test]
+    fn lookups() {
+        let mut table = RouteTable::default();
+        table.insert("/".into(), route("root"));
+        table.insert("/app/index.js".into(), route("index.js"));
+        table.insert_prefix("/app/", route("app"));
+        table.insert_prefix("/app/admin", route("admin"));
+
+        let cases: &[(&str, Option<&[u8]>)] = &[
+            ("/", Some(b"root")),
+            ("/app/index.js", Some(b"index.js")),
+            ("/app", Some(b"app")),
+            ("/app/", Some(b"app")),
+            ("/app/page", Some(b"app")),
+            ("/app/admin", Some(b"admin")),
+            ("/app/admin/users/1", Some(b"admin")),
+            ("/app/administration", Some(b"app")),
+            ("/application", None),
+            ("/other", None),
+        ];
+
+        for (path, expected) in cases {
+            assert_eq!(lookup(&table, path), *expected, "{path}");
+        }
+
+        table.set_fallback(Some(route("fallback")));
+
+        assert_eq!(lookup(&table, "/other"), Some(b"fallback".as_ref()));
+        assert_eq!(lookup(&table, "/app/page"), Some(b"app".as_ref()));
+    }
+
+    #[test]
+    fn hits_share_the_prepared_head() {
+        let mut server = crate::MemServer::default();
+        server.routes.insert("/".into(), route("root"));
+
+        let req = http::Request::builder().uri("/").body(()).unwrap();
+        let res = server.handle_request(req);
+
+        let prepared = &server.routes.get("/").unwrap().identity;
+        assert!(std::sync::Arc::ptr_eq(&res.head, &prepared.head));
+
+        let req = http::Request::builder()
+            .uri("/")
+            .header(http::header::RANGE, "bytes=0-1")
+            .body(())
+            .unwrap();
+        let res = server.handle_request(req);
+
+        assert_eq!(res.status(), http::StatusCode::PARTIAL_CONTENT);
+        assert_eq!(prepared.status(), http::StatusCode::OK);
+    }
+
+    #[test]
+    fn root_prefix() {
+        let mut table = RouteTable::default();
+        table.insert_prefix("/", route("root"));
+
+        assert_eq!(lookup(&table, "/"), Some(b"root".as_ref()));
+        assert_eq!(lookup(&table, "/a/b"), Some(b"root".as_ref()));
+    }
+}
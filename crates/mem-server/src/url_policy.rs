@@ -0,0 +1,219 @@
+//! The trailing slash and clean URLs policy.
+
+use crate::{Route, RouteTable};
+
+/// The trailing slash handling.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum TrailingSlash {
+    /// Match the paths as they are.
+    #[default]
+    Keep,
+
+    /// Redirect the paths without an extension to the form with the trailing slash.
+    Add,
+
+    /// Redirect the paths with the trailing slash to the form without it.
+    Remove,
+
+    /// Serve the paths both with and without the trailing slash.
+    Both,
+}
+
+/// The policy for the URL forms the routes are reachable at.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UrlPolicy {
+    /// The trailing slash handling.
+    pub trailing_slash: TrailingSlash,
+
+    /// Whether to serve the `.html` routes without the extension, redirecting the requests
+    /// with the extension, and the requests for the `index.html` of the directory routes,
+    /// to the form without it.
+    pub clean_urls: bool,
+}
+
+impl UrlPolicy {
+    /// Find the location to redirect the request with the given path and query to, if any.
+    pub fn redirect(
+        &self,
+        path: &str,
+        query: Option<&str>,
+        routes: &RouteTable,
+    ) -> Option<http::HeaderValue> {
+        let mut target = path;
+
+        if self.clean_urls {
+            if routes.contains(path) {
+                if let Some(stripped) = path.strip_suffix(".html") {
+                    target = stripped;
+                }
+            } else if let Some(dir) = path.strip_suffix("/index.html") {
+                let dir = if dir.is_empty() { "/" } else { dir };
+                if routes.contains(dir) {
+                    target = dir;
+                }
+            }
+        }
+
+        let target = match self.trailing_slash {
+            TrailingSlash::Keep => std::borrow::Cow::Borrowed(target),
+            TrailingSlash::Add if !target.ends_with('/') && !has_extension(target) => {
+                std::borrow::Cow::Owned(format!("{target}/"))
+            }
+            TrailingSlash::Add | TrailingSlash::Both => std::borrow::Cow::Borrowed(target),
+            TrailingSlash::Remove => {
+                let trimmed = target.trim_end_matches('/');
+                std::borrow::Cow::Borrowed(if trimmed.is_empty() { "/" } else { trimmed })
+            }
+        };
+
+        if target == path {
+            return None;
+        }
+
+        let target = crate::path::encode(&target);
+        let location = match query {
+            Some(query) if !query.is_empty() => format!("{target}?{query}"),
+            _ => target.into_owned(),
+        };
+
+        // The location is built from the encoded request path, which is a valid header value.
+        http::HeaderValue::try_from(location).ok()
+    }
+
+    /// Find the route for the given path, accounting for the trailing slash and clean URLs.
+    pub fn lookup<'a>(&self, path: &str, routes: &'a RouteTable) -> Option<&'a Route> {
+        let path = match self.trailing_slash {
+            TrailingSlash::Keep => path,
+            TrailingSlash::Add | TrailingSlash::Remove | TrailingSlash::Both => {
+                let trimmed = path.trim_end_matches('/');
+                if trimmed.is_empty() {
+                    "/"
+                } else {
+                    trimmed
+                }
+            }
+        };
+
+        if let Some(route) = routes.get(path) {
+            return Some(route);
+        }
+
+        if self.clean_urls && path != "/" {
+            return routes.get(&format!("{path}.html"));
+        }
+
+        None
+    }
+}
+
+/// Check whether the last segment of the path has an extension.
+fn has_extension(path: &str) -> bool {
+    let last_segment = path.rsplit('/').next().unwrap_or_default();
+    matches!(last_segment.rsplit_once('.'), Some((stem, _)) if !stem.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes() -> RouteTable {
+        let mut routes = RouteTable::default();
+        for path in ["/", "/about.html", "/nested", "/app.js"] {
+            routes.insert(
+                path.into(),
+                http::Response::new(bytes::Bytes::from_static(path.as_bytes())).into(),
+            );
+        }
+        routes
+    }
+
+    #[test]
+    fn redirects() {
+        let routes = routes();
+
+        let cases: &[(TrailingSlash, bool, &str, Option<&str>)] = &[
+            (TrailingSlash::Keep, false, "/about.html", None),
+            (TrailingSlash::Keep, true, "/about.html", Some("/about")),
+            (TrailingSlash::Keep, true, "/missing.html", None),
+            (TrailingSlash::Add, false, "/nested", Some("/nested/")),
+            (TrailingSlash::Add, false, "/nested/", None),
+            (TrailingSlash::Add, false, "/app.js", None),
+            (TrailingSlash::Add, true, "/about.html", Some("/about/")),
+            (TrailingSlash::Remove, false, "/nested/", Some("/nested")),
+            (TrailingSlash::Remove, false, "/nested//", Some("/nested")),
+            (TrailingSlash::Remove, false, "/", None),
+            (TrailingSlash::Remove, true, "/about.html", Some("/about")),
+            (TrailingSlash::Both, false, "/nested/", None),
+            (TrailingSlash::Both, false, "/nested", None),
+            (TrailingSlash::Both, true, "/about.html", Some("/about")),
+            (
+                TrailingSlash::Keep,
+                true,
+                "/nested/index.html",
+                Some("/nested"),
+            ),
+            (
+                TrailingSlash::Add,
+                true,
+                "/nested/index.html",
+                Some("/nested/"),
+            ),
+            (TrailingSlash::Keep, true, "/index.html", Some("/")),
+            (TrailingSlash::Keep, true, "/missing/index.html", None),
+        ];
+
+        for (trailing_slash, clean_urls, path, expected) in cases {
+            let policy = UrlPolicy {
+                trailing_slash: *trailing_slash,
+                clean_urls: *clean_urls,
+            };
+            assert_eq!(
+                policy.redirect(path, None, &routes),
+                expected.map(http::HeaderValue::from_static),
+                "{trailing_slash:?} {clean_urls} {path}"
+            );
+        }
+
+        let policy = UrlPolicy {
+            trailing_slash: TrailingSlash::Add,
+            clean_urls: false,
+        };
+        assert_eq!(
+            policy.redirect("/nested", Some("a=1"), &routes),
+            Some(http::HeaderValue::from_static("/nested/?a=1"))
+        );
+    }
+
+    #[test]
+    fn lookups() {
+        let routes = routes();
+
+        let cases: &[(TrailingSlash, bool, &str, Option<&[u8]>)] = &[
+            (TrailingSlash::Keep, false, "/nested", Some(b"/nested")),
+            (TrailingSlash::Keep, false, "/nested/", None),
+            (TrailingSlash::Add, false, "/nested/", Some(b"/nested")),
+            (TrailingSlash::Add, false, "/", Some(b"/")),
+            (TrailingSlash::Keep, false, "/about", None),
+            (TrailingSlash::Keep, true, "/about", Some(b"/about.html")),
+            (TrailingSlash::Add, true, "/about/", Some(b"/about.html")),
+            (TrailingSlash::Both, false, "/nested/", Some(b"/nested")),
+            (TrailingSlash::Both, false, "/nested", Some(b"/nested")),
+            (TrailingSlash::Both, true, "/about/", Some(b"/about.html")),
+        ];
+
+        for (trailing_slash, clean_urls, path, expected) in cases {
+            let policy = UrlPolicy {
+                trailing_slash: *trailing_slash,
+                clean_urls: *clean_urls,
+            };
+            assert_eq!(
+                policy
+                    .lookup(path, &routes)
+                    .map(|route| route.identity.body().as_ref()),
+                *expected,
+                "{trailing_slash:?} {clean_urls} {path}"
+            );
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Selecting the server by the requested host.
+
+use std::collections::HashMap;
+
+use crate::MemServer;
+
+/// The servers for the virtual hosts, keyed by the host name.
+///
+/// The host names are matched case-insensitively and without the port.
+#[derive(Debug, Default, Clone)]
+pub struct VirtualHosts(HashMap<String, MemServer>);
+
+impl VirtualHosts {
+    /// Get the server for the given host, adding an empty one if there is none.
+    pub fn get_or_default(&mut self, host: &str) -> &mut MemServer {
+        self.0.entry(normalize(host)).or_default()
+    }
+
+    /// Get the server for the given host.
+    pub fn get(&self, host: &str) -> Option<&MemServer> {
+        self.0.get(&normalize(host))
+    }
+
+    /// Iterate over the host names and their servers.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &MemServer)> {
+        self.0.iter().map(|(host, server)| (host.as_str(), server))
+    }
+
+    /// Iterate over the servers for modification.
+    pub fn servers_mut(&mut self) -> impl Iterator<Item = &mut MemServer> {
+        self.0.values_mut()
+    }
+
+    /// Whether there are no virtual hosts.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Select the server for the host the request is sent to.
+    ///
+    /// The host is taken from the request target authority, like the HTTP/2 `:authority`,
+    /// and from the `Host` header otherwise.
+    pub fn select<RequestBody>(&self, req: &http::Request<RequestBody>) -> Option<&MemServer> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let authority = match req.uri().authority() {
+            Some(authority) => authority.as_str(),
+            None => req.headers().get(http::header::HOST)?.to_str().ok()?,
+        };
+
+        self.get(strip_port(authority))
+    }
+}
+
+/// Normalize the host name for the comparison.
+fn normalize(host: &str) -> String {
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Strip the port and the user info from the authority.
+fn strip_port(authority: &str) -> &str {
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+
+    // The IPv6 addresses are enclosed in the brackets, and contain the colons themselves.
+    if host.starts_with('[') {
+        return host.split_inclusive(']').next().unwrap_or(host);
+    }
+
+    host.split_once(':').map_or(host, |(host, _)| host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(name: &'static str) -> MemServer {
+        let mut server = MemServer::default();
+        server.routes.insert(
+            "/".into(),
+            http::Response::new(bytes::Bytes::from_static(name.as_bytes())).into(),
+        );
+        server
+    }
+
+    fn select(hosts: &VirtualHosts, req: http::Request<()>) -> Option<&[u8]> {
+        hosts
+            .select(&req)
+            .and_then(|server| server.routes.get("/"))
+            .map(|route| route.identity.body().as_ref())
+    }
+
+    #[test]
+    fn selection() {
+        let mut hosts = VirtualHosts::default();
+        *hosts.get_or_default("Example.com") = server("example");
+        *hosts.get_or_default("[::1]") = server("ipv6");
+
+        let by_header = |host: &str| {
+            http::Request::builder()
+                .uri("/")
+                .header(http::header::HOST, host)
+                .body(())
+                .unwrap()
+        };
+
+        let cases: &[(&str, Option<&[u8]>)] = &[
+            ("example.com", Some(b"example")),
+            ("EXAMPLE.COM:8080", Some(b"example")),
+            ("example.com.", Some(b"example")),
+            ("[::1]:8080", Some(b"ipv6")),
+            ("other.com", None),
+        ];
+
+        for (host, expected) in cases {
+            assert_eq!(select(&hosts, by_header(host)), *expected, "{host}");
+        }
+
+        let by_authority = http::Request::builder()
+            .uri("https://example.com/")
+            .header(http::header::HOST, "other.com")
+            .body(())
+            .unwrap();
+        assert_eq!(select(&hosts, by_authority), Some(&b"example"[..]));
+
+        let no_host = http::Request::builder().uri("/").body(()).unwrap();
+        assert_eq!(select(&hosts, no_host), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! The `_headers` file parsing.
+//!
+//! The file consists of the blocks, each starting with an unindented path pattern line followed
+//! by the indented `Name: value` header lines:
+//!
+//! ```text
+//! /assets/*
+//!   Cache-Control: public, max-age=31536000, immutable
+//! /*
+//!   X-Frame-Options: DENY
+//! ```
+//!
+//! The `*` in the path pattern matches anything, and the `:name` segment matches any single
+//! segment. The header values repeated within a block are joined with commas.
+
+/// An error that can occur while parsing the `_headers` file.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The header line does not belong to any path.
+    #[error("line {0}: the header is not preceded by a path")]
+    NoPath(usize),
+
+    /// The path line is not a path.
+    #[error("line {0}: path {1:?} must start with a slash")]
+    InvalidPath(usize, String),
+
+    /// The header line is not in the `Name: value` form.
+    #[error("line {0}: expected the `Name: value` header")]
+    InvalidHeader(usize),
+
+    /// The header name is invalid.
+    #[error("line {0}: invalid header name: {1}")]
+    InvalidHeaderName(usize, http::header::InvalidHeaderName),
+
+    /// The header value is invalid.
+    #[error("line {0}: invalid header value: {1}")]
+    InvalidHeaderValue(usize, http::header::InvalidHeaderValue),
+}
+
+/// Parse the `_headers` file into the header rules.
+pub fn parse(data: &str) -> Result<yaml_headers::rules::Rules, Error> {
+    let mut blocks: Vec<(String, Vec<(http::HeaderName, String)>)> = Vec::new();
+
+    for (idx, line) in data.lines().enumerate() {
+        let line_number = idx + 1;
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if !line.starts_with(char::is_whitespace) {
+            if !trimmed.starts_with('/') {
+                return Err(Error::InvalidPath(line_number, trimmed.to_owned()));
+            }
+            blocks.push((trimmed.to_owned(), Vec::new()));
+            continue;
+        }
+
+        let Some((_, headers)) = blocks.last_mut() else {
+            return Err(Error::NoPath(line_number));
+        };
+
+        let (name, value) = trimmed
+            .split_once(':')
+            .ok_or(Error::InvalidHeader(line_number))?;
+        let name = http::HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|err| Error::InvalidHeaderName(line_number, err))?;
+        let value = value.trim();
+
+        match headers.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing)) => {
+                existing.push_str(", ");
+                existing.push_str(value);
+            }
+            None => headers.push((name, value.to_owned())),
+        }
+
+        // Validate early to report the right line.
+        http::HeaderValue::from_str(value)
+            .map_err(|err| Error::InvalidHeaderValue(line_number, err))?;
+    }
+
+    let rules = blocks
+        .into_iter()
+        .map(|(path, headers)| {
+            let set = headers
+                .into_iter()
+                .map(|(name, value)| {
+                    // The joined values consist of the valid values and commas.
+                    (name, http::HeaderValue::try_from(value).unwrap())
+                })
+                .collect();
+
+            yaml_headers::rules::Rule {
+                matcher: yaml_headers::rules::Matcher::Regex(path_regex(&path)),
+                remove: Vec::new(),
+                set,
+                append: http::HeaderMap::new(),
+            }
+        })
+        .collect();
+
+    Ok(yaml_headers::rules::Rules(rules))
+}
+
+/// Build the anchored regex for the path pattern.
+fn path_regex(path: &str) -> regex::Regex {
+    let mut regex = String::with_capacity(path.len() * 2 + 2);
+    regex.push('^');
+
+    for (idx, segment) in path.split('/').enumerate() {
+        if idx > 0 {
+            regex.push('/');
+        }
+
+        if segment.len() > 1 && segment.starts_with(':') {
+            regex.push_str("[^/]+");
+            continue;
+        }
+
+        let mut parts = segment.split('*');
+        if let Some(first) = parts.next() {
+            regex.push_str(&regex::escape(first));
+        }
+        for part in parts {
+            regex.push_str(".*");
+            regex.push_str(&regex::escape(part));
+        }
+    }
+
+    regex.push('$');
+    regex::Regex::new(&regex).unwrap() // the literal parts are escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "
+# Comment
+/assets/*
+  Cache-Control: public, max-age=31536000, immutable
+
+/*
+  X-Frame-Options: DENY
+  Link: </style.css>; rel=preload
+  Link: </app.js>; rel=preload
+
+/users/:id
+  X-User: yes
+";
+
+    fn apply(rules: &yaml_headers::rules::Rules, path: &str) -> Vec<(String, String)> {
+        let mut headers = http::HeaderMap::new();
+        rules.apply(&[path], &mut headers);
+        let mut headers: Vec<_> = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_str().unwrap().to_owned()))
+            .collect();
+        headers.sort();
+        headers
+    }
+
+    #[test]
+    fn parsing() {
+        let rules = parse(SAMPLE).unwrap();
+        assert_eq!(rules.0.len(), 3);
+
+        let link = (
+            "link".to_owned(),
+            "</style.css>; rel=preload, </app.js>; rel=preload".to_owned(),
+        );
+        let frame = ("x-frame-options".to_owned(), "DENY".to_owned());
+
+        assert_eq!(
+            apply(&rules, "/assets/js/main.js"),
+            vec![
+                (
+                    "cache-control".to_owned(),
+                    "public, max-age=31536000, immutable".to_owned()
+                ),
+                link.clone(),
+                frame.clone(),
+            ]
+        );
+        assert_eq!(apply(&rules, "/"), vec![link.clone(), frame.clone()]);
+        assert_eq!(
+            apply(&rules, "/users/1"),
+            vec![
+                link.clone(),
+                frame.clone(),
+                ("x-user".to_owned(), "yes".to_owned())
+            ]
+        );
+        assert_eq!(apply(&rules, "/users/1/posts"), vec![link, frame]);
+    }
+
+    #[test]
+    fn invalid() {
+        let cases: &[&str] = &[
+            "  X-Header: value",
+            "assets/*\n  X-Header: value",
+            "/*\n  X-Header",
+            "/*\n  Bad Header: value",
+            "/*\n  X-Header: \u{7f}",
+        ];
+
+        for sample in cases {
+            assert!(parse(sample).is_err(), "{sample:?}");
+        }
+    }
+}
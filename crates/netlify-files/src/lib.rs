@@ -0,0 +1,13 @@
+//! Parsing of the Netlify-style `_headers` and `_redirects` control files.
+//!
+//! See <https://docs.netlify.com/routing/headers/> and
+//! <https://docs.netlify.com/routing/redirects/>.
+
+pub mod headers;
+pub mod redirects;
+
+/// The name of the headers control file.
+pub const HEADERS_FILE: &str = "_headers";
+
+/// The name of the redirects control file.
+pub const REDIRECTS_FILE: &str = "_redirects";
@@ -0,0 +1,193 @@
+//! The `_redirects` file parsing.
+//!
+//! Every line is a rule in the `from to [status][!]` form, with the `301` status by default
+//! and the `!` forcing the rule to apply even when there is a route at the path:
+//!
+//! ```text
+//! /home          /                       301
+//! /blog/*        /news/:splat            302
+//! /news/:year/*  /archive/:year/:splat   200
+//! /app/*         /app.html               200!
+//! ```
+//!
+//! The query parameters, country, language and role conditions, as well as the proxying to
+//! the external URLs, are not supported, and the lines using them are skipped.
+
+use std::str::FromStr;
+
+/// An error that can occur while parsing the `_redirects` file.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The line does not have the target.
+    #[error("line {0}: expected the `from to [status]` form")]
+    MissingTarget(usize),
+
+    /// The line has more than the supported fields.
+    #[error("line {0}: unsupported field {1:?}, only `from to [status]` is supported")]
+    Unsupported(usize, String),
+
+    /// The status is invalid.
+    #[error("line {0}: invalid status {1:?}")]
+    InvalidStatus(usize, String),
+
+    /// The rule is invalid.
+    #[error("line {0}: {1}")]
+    Rule(usize, mem_server::RedirectError),
+}
+
+/// The parsed `_redirects` file.
+#[derive(Debug)]
+pub struct Parsed {
+    /// The rules from the supported lines.
+    pub redirects: mem_server::Redirects,
+
+    /// The reasons the other lines were skipped for.
+    pub skipped: Vec<Error>,
+}
+
+/// Parse the `_redirects` file into the redirect rules.
+///
+/// The lines that are malformed or use the unsupported features are skipped rather than
+/// failing the whole file, like Netlify does.
+pub fn parse(data: &str) -> Parsed {
+    let mut rules = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (line_number, line) in data.lines().enumerate() {
+        match parse_line(line_number + 1, line) {
+            Ok(Some(rule)) => rules.push(rule),
+            Ok(None) => {}
+            Err(err) => skipped.push(err),
+        }
+    }
+
+    Parsed {
+        redirects: mem_server::Redirects(rules),
+        skipped,
+    }
+}
+
+/// Parse a single line into the redirect rule, if it has one.
+fn parse_line(line_number: usize, line: &str) -> Result<Option<mem_server::RedirectRule>, Error> {
+    // The `#` only starts a comment at the start of a line or after a whitespace, so that
+    // the fragments in the targets are kept.
+    let line = line
+        .find(" #")
+        .or_else(|| line.find("\t#"))
+        .map_or(line, |idx| &line[..idx])
+        .trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let mut fields = line.split_whitespace();
+    let from = fields.next().unwrap_or_default();
+    let to = fields.next().ok_or(Error::MissingTarget(line_number))?;
+    let status = fields.next();
+
+    if let Some(field) = fields.next() {
+        return Err(Error::Unsupported(line_number, field.to_owned()));
+    }
+
+    let (status, force) = match status {
+        None => (http::StatusCode::MOVED_PERMANENTLY, false),
+        Some(status) => {
+            let (code, force) = match status.strip_suffix('!') {
+                Some(code) => (code, true),
+                None => (status, false),
+            };
+            let code = http::StatusCode::from_str(code)
+                .map_err(|_| Error::InvalidStatus(line_number, status.to_owned()))?;
+            (code, force)
+        }
+    };
+
+    let from = from.parse().map_err(|err| Error::Rule(line_number, err))?;
+    let rule = mem_server::RedirectRule::new(from, to.to_owned(), status, force)
+        .map_err(|err| Error::Rule(line_number, err))?;
+
+    Ok(Some(rule))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let parsed = parse(
+            "
+# Comment
+/home   /   # trailing comment
+/blog/*  /news/:splat  302
+/docs    /guide#intro
+/app/*   /app.html     200!
+",
+        );
+        assert!(parsed.skipped.is_empty(), "{:?}", parsed.skipped);
+
+        let rules: Vec<_> = parsed
+            .redirects
+            .0
+            .iter()
+            .map(|rule| (rule.to.as_str(), rule.status.as_u16(), rule.force))
+            .collect();
+
+        assert_eq!(
+            rules,
+            vec![
+                ("/", 301, false),
+                ("/news/:splat", 302, false),
+                ("/guide#intro", 301, false),
+                ("/app.html", 200, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let cases: &[&str] = &[
+            "/home",
+            "/home / 301 extra",
+            "/store id=:id /blog/:id 301",
+            "/home / abc",
+            "home / 301",
+            "/home https://example.com 200",
+        ];
+
+        for sample in cases {
+            let parsed = parse(sample);
+            assert!(parsed.redirects.0.is_empty(), "{sample:?}");
+            assert_eq!(parsed.skipped.len(), 1, "{sample:?}");
+        }
+    }
+
+    #[test]
+    fn skipped_lines() {
+        let parsed = parse(
+            "
+/old     /new
+/store   id=:id  /blog/:id  301
+/legacy  /current  302  Country=us
+/home    /
+",
+        );
+
+        let rules: Vec<_> = parsed
+            .redirects
+            .0
+            .iter()
+            .map(|rule| rule.to.as_str())
+            .collect();
+        assert_eq!(rules, ["/new", "/"]);
+
+        let lines: Vec<_> = parsed.skipped.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "line 3: unsupported field \"301\", only `from to [status]` is supported",
+                "line 4: unsupported field \"Country=us\", only `from to [status]` is supported",
+            ]
+        );
+    }
+}
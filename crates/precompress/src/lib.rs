@@ -0,0 +1,125 @@
+//! Ahead-of-time compression of the response bodies.
+
+use std::io::Write as _;
+
+use content_encoding::Encoding;
+
+/// The gzip compression level to use.
+const GZIP_LEVEL: u32 = 9;
+
+/// The brotli compression quality to use.
+const BROTLI_QUALITY: u32 = 11;
+
+/// The brotli window size (log2) to use.
+const BROTLI_LGWIN: u32 = 22;
+
+/// The zstd compression level to use.
+const ZSTD_LEVEL: i32 = 19;
+
+/// Compress the data with the given encoding.
+///
+/// Since the compression happens once at load time, the slowest but the most efficient
+/// settings are used.
+pub fn compress(encoding: Encoding, data: &[u8]) -> std::io::Result<Vec<u8>> {
+    match encoding {
+        Encoding::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(
+                Vec::with_capacity(data.len() / 2),
+                flate2::Compression::new(GZIP_LEVEL),
+            );
+            encoder.write_all(data)?;
+            encoder.finish()
+        }
+        Encoding::Brotli => {
+            let mut output = Vec::with_capacity(data.len() / 2);
+            {
+                let mut encoder =
+                    brotli::CompressorWriter::new(&mut output, 4096, BROTLI_QUALITY, BROTLI_LGWIN);
+                encoder.write_all(data)?;
+                encoder.flush()?;
+            }
+            Ok(output)
+        }
+        Encoding::Zstd => zstd::bulk::compress(data, ZSTD_LEVEL),
+    }
+}
+
+/// Check whether the content of the given media type benefits from compression.
+///
+/// Already compressed formats, like images, fonts and archives, are excluded.
+pub fn is_compressible(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    if mime.starts_with("text/") {
+        return true;
+    }
+
+    if mime.ends_with("+json") || mime.ends_with("+xml") {
+        return true;
+    }
+
+    matches!(
+        mime.as_str(),
+        "application/javascript"
+            | "application/json"
+            | "application/manifest+json"
+            | "application/wasm"
+            | "application/xml"
+            | "application/xhtml+xml"
+            | "application/x-javascript"
+            | "font/otf"
+            | "font/ttf"
+            | "image/bmp"
+            | "image/svg+xml"
+            | "image/vnd.microsoft.icon"
+            | "image/x-icon"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let data = b"hello hello hello hello hello hello hello hello".repeat(16);
+
+        for encoding in [Encoding::Gzip, Encoding::Brotli, Encoding::Zstd] {
+            let compressed = compress(encoding, &data).unwrap();
+            assert!(compressed.len() < data.len(), "{encoding:?}");
+
+            let decompressed = match encoding {
+                Encoding::Gzip => {
+                    let mut output = Vec::new();
+                    let mut decoder = flate2::write::GzDecoder::new(&mut output);
+                    decoder.write_all(&compressed).unwrap();
+                    decoder.finish().unwrap();
+                    output
+                }
+                Encoding::Brotli => {
+                    let mut output = Vec::new();
+                    brotli::BrotliDecompress(&mut compressed.as_slice(), &mut output).unwrap();
+                    output
+                }
+                Encoding::Zstd => zstd::bulk::decompress(&compressed, data.len()).unwrap(),
+            };
+
+            assert_eq!(decompressed, data, "{encoding:?}");
+        }
+    }
+
+    #[test]
+    fn compressible() {
+        assert!(is_compressible("text/html"));
+        assert!(is_compressible("text/css; charset=utf-8"));
+        assert!(is_compressible("application/javascript"));
+        assert!(is_compressible("image/svg+xml"));
+        assert!(!is_compressible("image/png"));
+        assert!(!is_compressible("font/woff2"));
+    }
+}
@@ -0,0 +1,115 @@
+//! Checking the health of the running server, for the container health checks.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+/// Request the health endpoint at the given path of the server listening at the given address,
+/// succeeding if it responds with a successful status within the timeout.
+pub async fn run(addr: SocketAddr, path: &str, timeout: Duration) -> color_eyre::eyre::Result<()> {
+    let addr = connectable(addr);
+
+    let Ok(res) = tokio::time::timeout(timeout, request(addr, path)).await else {
+        color_eyre::eyre::bail!("the server at {addr} did not respond within {timeout:?}");
+    };
+    let res = res?;
+
+    let Some(status) = parse_status(&res) else {
+        color_eyre::eyre::bail!("the server at {addr} responded with a malformed status line");
+    };
+    if !status.is_success() {
+        color_eyre::eyre::bail!("the server at {addr} is unhealthy: {status}");
+    }
+
+    tracing::info!(message = "The server is healthy", %addr, %status);
+
+    Ok(())
+}
+
+/// Send the request and read the response until the server closes the connection.
+async fn request(addr: SocketAddr, path: &str) -> std::io::Result<Vec<u8>> {
+    let mut stream = tokio::net::TcpStream::connect(addr).await?;
+    let req = format!("GET {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n");
+    stream.write_all(req.as_bytes()).await?;
+
+    let mut res = Vec::new();
+    stream.read_to_end(&mut res).await?;
+    Ok(res)
+}
+
+/// Replace the unspecified address the server listens at with the loopback one.
+fn connectable(mut addr: SocketAddr) -> SocketAddr {
+    match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => addr.set_ip(Ipv4Addr::LOCALHOST.into()),
+        IpAddr::V6(ip) if ip.is_unspecified() => addr.set_ip(Ipv6Addr::LOCALHOST.into()),
+        _ => {}
+    }
+    addr
+}
+
+/// Parse the status from the status line of the HTTP/1 response.
+fn parse_status(res: &[u8]) -> Option<http::StatusCode> {
+    let line = res.split(|&byte| byte == b'\n').next()?;
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let mut parts = line.split(|&byte| byte == b' ');
+    if !parts.next()?.starts_with(b"HTTP/1.") {
+        return None;
+    }
+    http::StatusCode::from_bytes(parts.next()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status() {
+        let cases: &[(&[u8], Option<u16>)] = &[
+            (
+                b"HTTP/1.1 200 OK\r\ncontent-length: 3\r\n\r\nok\n",
+                Some(200),
+            ),
+            (b"HTTP/1.1 503 Service Unavailable\r\n\r\n", Some(503)),
+            (b"HTTP/1.0 204\r\n\r\n", Some(204)),
+            (b"HTTP/1.1 200", Some(200)),
+            (b"", None),
+            (b"SSH-2.0-OpenSSH\r\n", None),
+            (b"HTTP/1.1 abc OK\r\n", None),
+        ];
+
+        for (res, expected) in cases {
+            assert_eq!(
+                parse_status(res).map(|status| status.as_u16()),
+                *expected,
+                "{:?}",
+                String::from_utf8_lossy(res)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn hanging_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let result = run(addr, "/__sap/healthz", Duration::from_millis(100)).await;
+        assert!(result.is_err());
+        drop(listener);
+    }
+
+    #[test]
+    fn unspecified_addresses() {
+        let cases = [
+            ("0.0.0.0:8080", "127.0.0.1:8080"),
+            ("[::]:8080", "[::1]:8080"),
+            ("10.0.0.1:80", "10.0.0.1:80"),
+        ];
+
+        for (addr, expected) in cases {
+            assert_eq!(connectable(addr.parse().unwrap()).to_string(), expected);
+        }
+    }
+}
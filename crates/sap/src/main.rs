@@ -1,6 +1,15 @@
 //! Main entrypoint.
 
-use std::sync::Arc;
+mod healthcheck;
+mod mounts;
+mod shutdown;
+mod site;
+#[cfg(target_os = "linux")]
+mod watch;
+
+use std::{sync::Arc, time::Duration};
+
+use xitca_web::service::ServiceExt;
 
 #[tokio::main]
 async fn main() -> color_eyre::eyre::Result<()> {
@@ -9,14 +18,48 @@ async fn main() -> color_eyre::eyre::Result<()> {
 
     let addr: std::net::SocketAddr = envfury::or_parse("ADDR", "0.0.0.0:8080")?;
 
+    // The mode given as the argument takes precedence, so that the health check command
+    // works regardless of the env.
+    let mode: Mode = match std::env::args().nth(1) {
+        Some(mode) => mode.parse()?,
+        None => envfury::or_else("MODE", Mode::default)?,
+    };
+
+    let liveness_path: String =
+        envfury::or_parse("LIVENESS_PATH", mem_server::DEFAULT_LIVENESS_PATH)?;
+    let readiness_path: String =
+        envfury::or_parse("READINESS_PATH", mem_server::DEFAULT_READINESS_PATH)?;
+    for path in [&liveness_path, &readiness_path] {
+        if !path.starts_with('/') {
+            color_eyre::eyre::bail!("the health endpoint path {path:?} must start with a slash");
+        }
+    }
+
+    if mode == Mode::Healthcheck {
+        let path: String = envfury::or_else("HEALTHCHECK_PATH", || liveness_path.clone())?;
+        let timeout: u64 = envfury::or("HEALTHCHECK_TIMEOUT", 5)?;
+        return healthcheck::run(addr, &path, Duration::from_secs(timeout)).await;
+    }
+
     let root_dir: std::path::PathBuf = envfury::must("ROOT_DIR")?;
 
     let max_file_size: spa_loader::FileSize =
         envfury::or("MAX_FILE_SIZE", spa_loader::FileSize::MAX)?;
 
-    let mode: Mode = envfury::or_else("MODE", Mode::default)?;
+    let shutdown_drain_timeout: u64 = envfury::or("SHUTDOWN_DRAIN_TIMEOUT", 30)?;
+    let shutdown_pre_stop_delay: u64 = envfury::or("SHUTDOWN_PRE_STOP_DELAY", 0)?;
+
+    let base_path: String = envfury::or_parse("BASE_PATH", "")?;
+    let base_path = base_path.trim_end_matches('/');
+    if !base_path.is_empty() && !base_path.starts_with('/') {
+        color_eyre::eyre::bail!("the base path {base_path:?} must start with a slash");
+    }
 
     let root_as_not_found: bool = envfury::or("ROOT_AS_NOT_FOUND", true)?;
+    let not_found_status: http::StatusCode = envfury::or_parse("NOT_FOUND_STATUS", "200")?;
+    let not_found_headers: yaml_headers::Headers = envfury::or_parse("NOT_FOUND_HEADERS", "")?;
+
+    let error_pages: spa_loader::error_page::List = envfury::or_parse("ERROR_PAGES", "")?;
 
     let root_templating: RootTemplating =
         envfury::or_else("ROOT_TEMPLATING", RootTemplating::default)?;
@@ -33,45 +76,234 @@ async fn main() -> color_eyre::eyre::Result<()> {
         global_headers.0.extend(parsed.0);
     }
 
+    let mut header_rules: yaml_headers::rules::Rules = envfury::or_parse("HEADER_RULES", "")?;
+    let header_rules_file: Option<std::path::PathBuf> = envfury::maybe("HEADER_RULES_FILE")?;
+
+    if let Some(path) = header_rules_file {
+        let data = tokio::fs::read_to_string(path).await?;
+        let parsed: yaml_headers::rules::Rules = data.parse()?;
+        header_rules.0.extend(parsed.0);
+    }
+
+    let cache_hashed_files: bool = envfury::or("CACHE_HASHED_FILES", false)?;
+    let hashed_file_patterns: spa_loader::cache_policy::HashedPatterns = envfury::or_parse(
+        "HASHED_FILE_PATTERNS",
+        spa_loader::cache_policy::DEFAULT_HASHED_PATTERN,
+    )?;
+    let cache_control_hashed: http::HeaderValue = envfury::or_parse(
+        "CACHE_CONTROL_HASHED",
+        spa_loader::cache_policy::DEFAULT_HASHED,
+    )?;
+    let cache_control_revalidate: http::HeaderValue = envfury::or_parse(
+        "CACHE_CONTROL_REVALIDATE",
+        spa_loader::cache_policy::DEFAULT_REVALIDATE,
+    )?;
+
+    let mut redirects: yaml_redirects::Redirects = envfury::or_parse("REDIRECTS", "")?;
+    let redirects_file: Option<std::path::PathBuf> = envfury::maybe("REDIRECTS_FILE")?;
+
+    if let Some(path) = redirects_file {
+        let data = tokio::fs::read_to_string(path).await?;
+        let parsed: yaml_redirects::Redirects = data.parse()?;
+        redirects.0.extend(parsed.0);
+    }
+
+    let precompress: content_encoding::List = envfury::or_parse("PRECOMPRESS", "")?;
+    let netlify_files: bool = envfury::or("NETLIFY_FILES", false)?;
+    let serve_json: bool = envfury::or("SERVE_JSON", false)?;
+    let precompressed_siblings: bool = envfury::or("PRECOMPRESSED_SIBLINGS", false)?;
+
+    let trailing_slash: Option<mem_server::TrailingSlash> = envfury::maybe("TRAILING_SLASH")?;
+    let clean_urls: Option<bool> = envfury::maybe("CLEAN_URLS")?;
+
+    let canonical_origin: Option<mem_server::CanonicalOrigin> = envfury::maybe("CANONICAL_ORIGIN")?;
+    let canonical_redirect_status: http::StatusCode =
+        envfury::or_parse("CANONICAL_REDIRECT_STATUS", "301")?;
+    if !matches!(canonical_redirect_status.as_u16(), 301 | 302 | 307 | 308) {
+        color_eyre::eyre::bail!(
+            "the canonical redirect status {canonical_redirect_status} is not one of 301, 302, 307 or 308"
+        );
+    }
+    let trusted_proxies: mem_server::TrustedProxies = envfury::or_parse("TRUSTED_PROXIES", "")?;
+
+    let allowed_methods: mem_server::AllowedMethods =
+        envfury::or_parse("ALLOWED_METHODS", "GET,HEAD,OPTIONS")?;
+
+    let assets_by_extension: bool = envfury::or("ASSETS_BY_EXTENSION", false)?;
+    let asset_prefixes: mem_server::AssetPrefixes = envfury::or_parse("ASSET_PREFIXES", "")?;
+    let navigation_requires_html_accept: bool =
+        envfury::or("NAVIGATION_REQUIRES_HTML_ACCEPT", false)?;
+
+    let mut mounts: mounts::Mounts = envfury::or_parse("MOUNTS", "")?;
+    let mounts_file: Option<std::path::PathBuf> = envfury::maybe("MOUNTS_FILE")?;
+
+    if let Some(path) = mounts_file {
+        let data = tokio::fs::read_to_string(path).await?;
+        let parsed: mounts::Mounts = data.parse()?;
+        mounts.0.extend(parsed.0);
+    }
+
     let loader = spa_loader::Loader {
         max_file_size,
         root_dir,
         root_as_not_found,
-        root_templating: (!matches!(root_templating, RootTemplating::Disabled)).then_some(
-            spa_cfg_html::Engine {
-                env_prefix: std::borrow::Cow::Owned(cfg_env_prefix.clone()),
-                template_tag_presence: match root_templating {
-                    RootTemplating::Auto => spa_cfg_html::TemplateTagPresence::SkipIfNotFound,
-                    RootTemplating::Force => spa_cfg_html::TemplateTagPresence::Required,
-                    RootTemplating::Disabled => unreachable!(),
-                },
-            },
-        ),
-        config_json_templating: config_json_templating.then_some(spa_cfg_json::Engine {
-            env_prefix: std::borrow::Cow::Owned(cfg_env_prefix),
+        not_found_status,
+        not_found_headers: not_found_headers.into(),
+        error_pages: error_pages.0,
+        mount_prefix: base_path.to_owned(),
+        base_href: !base_path.is_empty(),
+        root_templating: root_templating.engine(&cfg_env_prefix),
+        config_json_templating: config_json_templating.then(|| spa_cfg_json::Engine {
+            env_prefix: std::borrow::Cow::Owned(cfg_env_prefix.clone()),
         }),
         headers: global_headers.into(),
+        header_rules,
+        cache_policy: cache_hashed_files.then_some(spa_loader::cache_policy::CachePolicy {
+            hashed_patterns: hashed_file_patterns,
+            hashed: cache_control_hashed,
+            revalidate: cache_control_revalidate,
+        }),
+        precompress: precompress.0,
+        netlify_files,
+        serve_json,
+        serve_json_url_policy: true,
+        precompressed_siblings,
+        html_script: (mode == Mode::Dev)
+            .then(|| xitca_mem_server::live_reload::CLIENT_SCRIPT.to_owned()),
+    };
+
+    let navigation = mem_server::NavigationFilter {
+        assets_by_extension,
+        asset_prefixes,
+        require_html_accept: navigation_requires_html_accept,
     };
 
-    tracing::info!(message = "Loading the files into memory", ?loader);
+    let site = site::Site {
+        loader,
+        base_path: base_path.to_owned(),
+        mounts: mounts.0,
+        cfg_env_prefix,
+        allowed_methods,
+        navigation,
+        redirects: redirects.into(),
+        trailing_slash,
+        clean_urls,
+        canonicalization: canonical_origin.map(|origin| mem_server::Canonicalization {
+            origin,
+            status: canonical_redirect_status,
+            trusted_proxies,
+        }),
+        health: mem_server::Health {
+            liveness_path,
+            readiness_path,
+            readiness: Default::default(),
+        },
+        reserved_paths: (mode == Mode::Dev)
+            .then(|| xitca_mem_server::live_reload::EVENTS_PATH.to_owned())
+            .into_iter()
+            .collect(),
+    };
 
-    let service = loader.load().await?;
+    let service = site.load(mode == Mode::Check).await?;
 
     if mode == Mode::Check {
         return Ok(());
     }
 
-    let service = xitca_mem_server::Service(Arc::new(service));
+    let mut service = xitca_mem_server::Service::new(service);
+
+    let readiness = site.health.readiness.clone();
+    readiness.set_loaded(true);
 
-    let server = xitca_web::HttpServer::serve(service).bind(addr)?;
+    let site = Arc::new(site);
+
+    if mode == Mode::Dev {
+        service = service.with_live_reload(Default::default());
+        spawn_watch(Arc::clone(&site), service.clone())?;
+    }
+
+    #[cfg(unix)]
+    tokio::spawn(reload_on_sighup(site, service.clone()));
+
+    let signals = shutdown::Signals::new()?;
+
+    let mut server = xitca_server::Builder::new()
+        .disable_signal()
+        .shutdown_timeout(shutdown_drain_timeout)
+        .bind(
+            "sap",
+            addr,
+            ServiceExt::<()>::enclosed(Arc::new(service), xitca_http::HttpServiceBuilder::new()),
+        )?
+        .build();
+
+    tokio::spawn(shutdown::on_signals(
+        signals,
+        server.handle()?,
+        readiness,
+        Duration::from_secs(shutdown_pre_stop_delay),
+    ));
 
     tracing::info!(message = "Server is ready", %addr);
 
-    server.run().await?;
+    server.await?;
+
+    tracing::info!(message = "Server stopped");
 
     Ok(())
 }
 
+/// Reload the site on every `SIGHUP`.
+#[cfg(unix)]
+async fn reload_on_sighup(site: Arc<site::Site>, service: xitca_mem_server::Service) {
+    let mut hangups = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(error) => {
+            tracing::error!(message = "Unable to listen for SIGHUP, reloading is disabled", %error);
+            return;
+        }
+    };
+
+    while hangups.recv().await.is_some() {
+        site.reload(&service).await;
+    }
+}
+
+/// Update the site whenever the files in the root dirs change.
+#[cfg(target_os = "linux")]
+fn spawn_watch(
+    site: Arc<site::Site>,
+    service: xitca_mem_server::Service,
+) -> color_eyre::eyre::Result<()> {
+    let dirs = site.root_dirs();
+    tracing::info!(message = "Watching for changes", ?dirs);
+    let mut watcher = watch::Watcher::new(dirs)?;
+
+    tokio::spawn(async move {
+        loop {
+            let changed = match watcher.changed().await {
+                Ok(changed) => changed,
+                Err(error) => {
+                    tracing::error!(message = "Watching for changes failed, reloading is disabled", %error);
+                    return;
+                }
+            };
+            site.update(&service, &changed).await;
+        }
+    });
+
+    Ok(())
+}
+
+/// Update the site whenever the files in the root dirs change.
+#[cfg(not(target_os = "linux"))]
+fn spawn_watch(
+    _site: Arc<site::Site>,
+    _service: xitca_mem_server::Service,
+) -> color_eyre::eyre::Result<()> {
+    color_eyre::eyre::bail!("the dev mode is only supported on Linux")
+}
+
 /// The operation mode.
 #[derive(Debug, PartialEq, Eq, Default, strum::EnumString)]
 #[strum(serialize_all = "snake_case")]
@@ -81,11 +313,16 @@ enum Mode {
     Run,
     /// Load the SPA and exit.
     Check,
+    /// Run the server, reloading the SPA and the pages in the browsers on changes.
+    Dev,
+    /// Check the health of the running server and exit with the result.
+    Healthcheck,
 }
 
 /// The mode of root templating.
-#[derive(Debug, PartialEq, Eq, Default, strum::EnumString)]
+#[derive(Debug, PartialEq, Eq, Default, strum::EnumString, serde::Deserialize)]
 #[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 enum RootTemplating {
     /// Templatify if the the script template tag is found.
     #[default]
@@ -95,3 +332,18 @@ enum RootTemplating {
     /// Do not attempt templatifying.
     Disabled,
 }
+
+impl RootTemplating {
+    /// The templating engine for the root page, if enabled.
+    fn engine(&self, cfg_env_prefix: &str) -> Option<spa_cfg_html::Engine> {
+        let template_tag_presence = match self {
+            Self::Auto => spa_cfg_html::TemplateTagPresence::SkipIfNotFound,
+            Self::Force => spa_cfg_html::TemplateTagPresence::Required,
+            Self::Disabled => return None,
+        };
+        Some(spa_cfg_html::Engine {
+            env_prefix: std::borrow::Cow::Owned(cfg_env_prefix.to_owned()),
+            template_tag_presence,
+        })
+    }
+}
@@ -0,0 +1,119 @@
+//! The SPAs mounted under the path prefixes.
+
+use std::str::FromStr;
+
+/// The list of the mounts.
+///
+/// ```yaml
+/// - prefix: /admin
+///   root_dir: /admin-app
+///   root_templating: force
+///   headers:
+///     X-Frame-Options: DENY
+/// - host: promo.example.com
+///   root_dir: /promo-app
+/// ```
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Mounts(pub Vec<Mount>);
+
+impl FromStr for Mounts {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let val: Option<Self> = serde_yaml::from_str(s)?;
+        Ok(val.unwrap_or_default())
+    }
+}
+
+/// An SPA mounted under the path prefix, optionally at a virtual host.
+///
+/// The settings that are not specified are inherited from the main SPA.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Mount {
+    /// The virtual host to serve the SPA at, like `example.com`.
+    ///
+    /// The requests to the hosts that are not listed are served by the main SPA.
+    pub host: Option<String>,
+
+    /// The path prefix to serve the SPA under, like `/admin`.
+    ///
+    /// Relative to the base path for the main host. Can only be omitted for the virtual hosts,
+    /// to serve the SPA at the root.
+    #[serde(default)]
+    pub prefix: String,
+
+    /// The directory to load the SPA from.
+    pub root_dir: std::path::PathBuf,
+
+    /// Whether to point the `<base href>` of the root page at the prefix.
+    ///
+    /// Inherited for the main host and disabled by default for the virtual hosts.
+    pub base_href: Option<bool>,
+
+    /// Whether to use the root page as not found for the paths under the prefix.
+    pub root_as_not_found: Option<bool>,
+
+    /// The status of the not found response.
+    #[serde(default, with = "http_serde::option::status_code")]
+    pub not_found_status: Option<http::StatusCode>,
+
+    /// The mode of the root page templating.
+    pub root_templating: Option<crate::RootTemplating>,
+
+    /// Whether to template the `config.json`.
+    pub config_json_templating: Option<bool>,
+
+    /// The headers to add to the responses, on top of the global ones.
+    pub headers: Option<yaml_headers::Headers>,
+
+    /// The per-path header rules, applied after the global ones.
+    pub header_rules: Option<yaml_headers::rules::Rules>,
+}
+
+impl Mount {
+    /// The prefix without the trailing slash, or an error if it is not a proper prefix.
+    pub fn normalized_prefix(&self) -> color_eyre::eyre::Result<&str> {
+        let prefix = self.prefix.trim_end_matches('/');
+        if prefix.is_empty() && self.host.is_some() {
+            return Ok(prefix);
+        }
+        if !prefix.starts_with('/') {
+            color_eyre::eyre::bail!(
+                "the mount prefix {:?} must start with a slash and not be the root",
+                self.prefix
+            );
+        }
+        Ok(prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let mounts = Mounts::from_str(
+            "- prefix: /admin/\n  root_dir: /admin\n  not_found_status: 404\n  root_templating: disabled\n  headers:\n    x-frame-options: DENY\n",
+        )
+        .unwrap();
+        let [mount] = mounts.0.as_slice() else {
+            panic!("expected a single mount: {mounts:?}");
+        };
+        assert_eq!(mount.normalized_prefix().unwrap(), "/admin");
+        assert_eq!(mount.not_found_status, Some(http::StatusCode::NOT_FOUND));
+        assert_eq!(mount.root_templating, Some(crate::RootTemplating::Disabled));
+        assert_eq!(mount.root_as_not_found, None);
+
+        assert!(Mounts::from_str("").unwrap().0.is_empty());
+        assert!(Mounts::from_str("- prefix: /a\n  root_dir: /a\n  unknown: 1\n").is_err());
+
+        let root = Mounts::from_str("- prefix: /\n  root_dir: /a\n").unwrap();
+        assert!(root.0[0].normalized_prefix().is_err());
+
+        let hosted = Mounts::from_str("- host: example.com\n  root_dir: /a\n").unwrap();
+        assert_eq!(hosted.0[0].normalized_prefix().unwrap(), "");
+    }
+}
@@ -0,0 +1,80 @@
+//! The graceful shutdown on the termination signals.
+
+use std::time::Duration;
+
+/// The termination signals to listen for.
+pub struct Signals {
+    /// The `SIGTERM` stream.
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+
+    /// The `SIGINT` stream.
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    /// Start listening for the termination signals.
+    pub fn new() -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            Ok(Self {
+                terminate: signal(SignalKind::terminate())?,
+                interrupt: signal(SignalKind::interrupt())?,
+            })
+        }
+
+        #[cfg(not(unix))]
+        Ok(Self {})
+    }
+
+    /// Wait for the next termination signal and return its name.
+    async fn recv(&mut self) -> &'static str {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.terminate.recv() => "SIGTERM",
+                _ = self.interrupt.recv() => "SIGINT",
+            }
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = tokio::signal::ctrl_c().await;
+            "Ctrl-C"
+        }
+    }
+}
+
+/// Shut the server down on the first termination signal.
+///
+/// The readiness is dropped first, and the server keeps serving for the pre-stop delay so that
+/// the load balancers have the time to stop routing the traffic to it. Then the server stops
+/// accepting the connections and drains the ones in flight. A second signal skips the pre-stop
+/// delay, or, when already draining, exits right away.
+pub async fn on_signals(
+    mut signals: Signals,
+    server: xitca_server::ServerHandle,
+    readiness: mem_server::Readiness,
+    pre_stop_delay: Duration,
+) {
+    let signal = signals.recv().await;
+    readiness.set_stopping();
+    tracing::info!(message = "Shutting down, reporting not ready", %signal, ?pre_stop_delay);
+
+    tokio::select! {
+        _ = tokio::time::sleep(pre_stop_delay) => {}
+        signal = signals.recv() => {
+            tracing::info!(message = "Skipping the pre-stop delay", %signal);
+        }
+    }
+
+    tracing::info!(message = "Draining the connections");
+    server.stop(true);
+
+    let signal = signals.recv().await;
+    tracing::warn!(message = "Exiting without draining the connections", %signal);
+    std::process::exit(1);
+}
@@ -0,0 +1,275 @@
+//! Loading the site to serve.
+
+use std::path::PathBuf;
+
+use crate::mounts;
+
+/// Everything needed to load the site to serve, so that it can be reloaded.
+#[derive(Debug)]
+pub struct Site {
+    /// The loader for the main SPA.
+    pub loader: spa_loader::Loader,
+
+    /// The path prefix the main SPA is served under, without the trailing slash.
+    pub base_path: String,
+
+    /// The SPAs mounted alongside the main one.
+    pub mounts: Vec<mounts::Mount>,
+
+    /// The prefix for the templating env vars.
+    pub cfg_env_prefix: String,
+
+    /// The HTTP methods to respond to.
+    pub allowed_methods: mem_server::AllowedMethods,
+
+    /// The rules for telling the navigation requests apart.
+    pub navigation: mem_server::NavigationFilter,
+
+    /// The configured redirect and rewrite rules, taking precedence over the ones from
+    /// the root dirs.
+    pub redirects: mem_server::Redirects,
+
+    /// The configured trailing slash handling, overriding the one from the `serve.json`.
+    pub trailing_slash: Option<mem_server::TrailingSlash>,
+
+    /// The configured clean URLs setting, overriding the one from the `serve.json`.
+    pub clean_urls: Option<bool>,
+
+    /// The redirects to the canonical origin for the main host.
+    pub canonicalization: Option<mem_server::Canonicalization>,
+
+    /// The health endpoints, with the readiness tracking the loading of the site.
+    pub health: mem_server::Health,
+
+    /// The other paths answered ahead of the routes for every host, like the live reload
+    /// events in the dev mode.
+    pub reserved_paths: Vec<String>,
+}
+
+impl Site {
+    /// The directories the SPAs are loaded from.
+    pub fn root_dirs(&self) -> Vec<std::path::PathBuf> {
+        std::iter::once(&self.loader.root_dir)
+            .chain(self.mounts.iter().map(|mount| &mount.root_dir))
+            .cloned()
+            .collect()
+    }
+
+    /// Load the site again and swap it in, keeping the current one in service if the loading
+    /// fails.
+    ///
+    /// The readiness is left as is on a failure, as the current site is still served, so that
+    /// a broken push to the shared root dir does not take every replica out of service.
+    /// The failure is reported in the readiness endpoint body instead.
+    pub async fn reload(&self, service: &xitca_mem_server::Service) {
+        tracing::info!(message = "Reloading the site");
+        match self.load(false).await {
+            Ok(server) => {
+                service.swap(server);
+                self.health.readiness.set_reload_failed(false);
+                tracing::info!(message = "Site reloaded");
+            }
+            Err(error) => {
+                self.health.readiness.set_reload_failed(true);
+                tracing::error!(message = "Reloading failed, keeping the current site", %error);
+            }
+        }
+    }
+
+    /// Load the changed files again into a copy of the current site and swap it in, loading
+    /// the whole site again when the changes can not be applied this way.
+    ///
+    /// Only the changed files are precompressed again, so the precompression stays cheap
+    /// enough for the dev mode.
+    pub async fn update(&self, service: &xitca_mem_server::Service, changed: &[PathBuf]) {
+        tracing::info!(message = "Updating the site", ?changed);
+        let mut server = mem_server::MemServer::clone(&service.server());
+        match self.update_server(&mut server, changed).await {
+            Ok(true) => {
+                service.swap(server);
+                self.health.readiness.set_reload_failed(false);
+                tracing::info!(message = "Site updated");
+            }
+            Ok(false) => self.reload(service).await,
+            Err(error) => {
+                self.health.readiness.set_reload_failed(true);
+                tracing::error!(message = "Updating failed, keeping the current site", %error);
+            }
+        }
+    }
+
+    /// Load the changed files again into the server, returning `false` when the whole site
+    /// has to be loaded again instead.
+    async fn update_server(
+        &self,
+        server: &mut mem_server::MemServer,
+        changed: &[PathBuf],
+    ) -> color_eyre::eyre::Result<bool> {
+        let mut loaders = vec![(self.loader.clone(), None, Vec::new())];
+        for mount in &self.mounts {
+            loaders.push((self.mount_loader(mount)?, mount.host.as_deref(), Vec::new()));
+        }
+
+        // The changed file belongs to the SPA with the innermost root dir.
+        for path in changed {
+            let loader = loaders
+                .iter_mut()
+                .filter(|(loader, ..)| path.starts_with(&loader.root_dir))
+                .max_by_key(|(loader, ..)| loader.root_dir.components().count());
+            let Some((.., paths)) = loader else {
+                return Ok(false);
+            };
+            paths.push(path.clone());
+        }
+
+        for (loader, host, paths) in loaders {
+            if paths.is_empty() {
+                continue;
+            }
+            let target = match host {
+                Some(host) => server.virtual_hosts.get_or_default(host),
+                None => &mut *server,
+            };
+            if !loader.update(target, &paths).await? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Load the files into memory and prepare the server.
+    ///
+    /// When strict, the rewrite targets that do not resolve to any route and the routes
+    /// shadowed by the health and the other reserved endpoints are an error rather than
+    /// a warning.
+    pub async fn load(&self, strict: bool) -> color_eyre::eyre::Result<mem_server::MemServer> {
+        let Self { loader, mounts, .. } = self;
+
+        tracing::info!(message = "Loading the files into memory", ?loader);
+
+        let mut service = loader.load().await?;
+
+        for mount in mounts {
+            let mount_loader = self.mount_loader(mount)?;
+
+            tracing::info!(message = "Loading the mount into memory", loader = ?mount_loader);
+
+            let target = match &mount.host {
+                Some(host) => service.virtual_hosts.get_or_default(host),
+                None => &mut service,
+            };
+            mount_loader.load_into(target).await?;
+        }
+
+        let mut virtual_hosts = std::mem::take(&mut service.virtual_hosts);
+        for server in std::iter::once(&mut service).chain(virtual_hosts.servers_mut()) {
+            server.allowed_methods = self.allowed_methods.clone();
+            server.navigation = self.navigation.clone();
+
+            // The configured URL policy takes precedence over the one from the `serve.json`.
+            if let Some(trailing_slash) = self.trailing_slash {
+                server.url_policy.trailing_slash = trailing_slash;
+            }
+            if let Some(clean_urls) = self.clean_urls {
+                server.url_policy.clean_urls = clean_urls;
+            }
+
+            // The configured rules take precedence over the ones from the root dir.
+            let mut rules = self.redirects.0.clone();
+            rules.append(&mut server.redirects.0);
+            server.redirects.0 = rules;
+        }
+        service.virtual_hosts = virtual_hosts;
+
+        // The virtual hosts are served at their own origins, so only the main host
+        // is canonicalized.
+        service.canonicalization = self.canonicalization.clone();
+
+        // The health endpoints are answered for every host by the main server.
+        service.health = Some(self.health.clone());
+
+        let hosts = std::iter::once(("", &service)).chain(service.virtual_hosts.iter());
+        for (host, server) in hosts {
+            let reserved_paths = self
+                .health
+                .paths()
+                .into_iter()
+                .chain(self.reserved_paths.iter().map(String::as_str));
+            let shadowed_routes = server.shadowed_routes(reserved_paths);
+            if !shadowed_routes.is_empty() {
+                if strict {
+                    color_eyre::eyre::bail!(
+                        "the reserved endpoints shadow the routes at the host {host:?}: {shadowed_routes:?}"
+                    );
+                }
+                tracing::warn!(
+                    message = "The reserved endpoints shadow the routes",
+                    %host,
+                    ?shadowed_routes
+                );
+            }
+
+            let unresolved_rewrites = server.unresolved_rewrites();
+            if unresolved_rewrites.is_empty() {
+                continue;
+            }
+            if strict {
+                color_eyre::eyre::bail!(
+                    "the rewrite targets do not resolve to any route at the host {host:?}: {unresolved_rewrites:?}"
+                );
+            }
+            tracing::warn!(
+                message = "The rewrite targets do not resolve to any route",
+                %host,
+                ?unresolved_rewrites
+            );
+        }
+
+        Ok(service)
+    }
+
+    /// The loader for the SPA mounted alongside the main one.
+    fn mount_loader(&self, mount: &mounts::Mount) -> color_eyre::eyre::Result<spa_loader::Loader> {
+        let mut mount_loader = self.loader.clone();
+        let prefix = mount.normalized_prefix()?;
+        // The base path only applies to the default host.
+        let (mount_prefix, base_href) = match mount.host {
+            Some(_) => (prefix.to_owned(), false),
+            None => (format!("{}{prefix}", self.base_path), self.loader.base_href),
+        };
+        // Only the SPA at the root of its host sets the URL policy of the host.
+        mount_loader.serve_json_url_policy = mount.host.is_some() && prefix.is_empty();
+        mount_loader.mount_prefix = mount_prefix;
+        mount_loader.base_href = mount.base_href.unwrap_or(base_href);
+        mount_loader.root_dir.clone_from(&mount.root_dir);
+        // The error pages are served for the whole server, so they only come from
+        // the main SPA.
+        mount_loader.error_pages = Vec::new();
+        if let Some(root_as_not_found) = mount.root_as_not_found {
+            mount_loader.root_as_not_found = root_as_not_found;
+        }
+        if let Some(not_found_status) = mount.not_found_status {
+            mount_loader.not_found_status = not_found_status;
+        }
+        if let Some(root_templating) = &mount.root_templating {
+            mount_loader.root_templating = root_templating.engine(&self.cfg_env_prefix);
+        }
+        if let Some(config_json_templating) = mount.config_json_templating {
+            mount_loader.config_json_templating =
+                config_json_templating.then(|| spa_cfg_json::Engine {
+                    env_prefix: std::borrow::Cow::Owned(self.cfg_env_prefix.clone()),
+                });
+        }
+        if let Some(headers) = &mount.headers {
+            mount_loader.headers.extend(headers.0.clone());
+        }
+        if let Some(header_rules) = &mount.header_rules {
+            mount_loader
+                .header_rules
+                .0
+                .extend(header_rules.0.iter().cloned());
+        }
+        Ok(mount_loader)
+    }
+}
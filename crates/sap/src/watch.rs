@@ -0,0 +1,116 @@
+//! Watching the directories for changes with inotify.
+
+use std::{collections::HashMap, path::PathBuf, pin::Pin, time::Duration};
+
+/// How long to wait for more changes before reporting them, so that a burst of changes,
+/// like a rebuild, is reported once.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// The kinds of changes to watch for.
+const MASK: inotify::WatchMask = inotify::WatchMask::CLOSE_WRITE
+    .union(inotify::WatchMask::CREATE)
+    .union(inotify::WatchMask::DELETE)
+    .union(inotify::WatchMask::DELETE_SELF)
+    .union(inotify::WatchMask::MOVED_FROM)
+    .union(inotify::WatchMask::MOVED_TO);
+
+/// The recursive watcher of the directories.
+pub struct Watcher {
+    /// The directories to watch, along with all of their subdirectories.
+    dirs: Vec<PathBuf>,
+
+    /// The watches of the inotify instance.
+    watches: inotify::Watches,
+
+    /// The watched directories by their watches.
+    watched: HashMap<inotify::WatchDescriptor, PathBuf>,
+
+    /// The inotify events.
+    events: inotify::EventStream<Vec<u8>>,
+}
+
+impl Watcher {
+    /// Start watching the directories.
+    pub fn new(dirs: Vec<PathBuf>) -> std::io::Result<Self> {
+        let inotify = inotify::Inotify::init()?;
+        let watches = inotify.watches();
+        let events = inotify.into_event_stream(vec![0; 4096])?;
+
+        let mut watcher = Self {
+            dirs,
+            watches,
+            watched: HashMap::new(),
+            events,
+        };
+        watcher.add_watches()?;
+        Ok(watcher)
+    }
+
+    /// Wait for the changes to the files in the directories, returning the changed paths.
+    ///
+    /// The events that can not be attributed to a path, like the event queue overflow,
+    /// are reported as the change to the first of the directories.
+    pub async fn changed(&mut self) -> std::io::Result<Vec<PathBuf>> {
+        let mut changed = std::collections::BTreeSet::new();
+        changed.insert(self.next_event().await?);
+
+        while let Ok(event) = tokio::time::timeout(DEBOUNCE, self.next_event()).await {
+            changed.insert(event?);
+        }
+
+        // The new subdirectories have to be watched too.
+        self.add_watches()?;
+
+        Ok(changed.into_iter().collect())
+    }
+
+    /// Wait for the next inotify event, returning the changed path.
+    async fn next_event(&mut self) -> std::io::Result<PathBuf> {
+        let event = std::future::poll_fn(|cx| {
+            futures_core::Stream::poll_next(Pin::new(&mut self.events), cx)
+        })
+        .await;
+
+        let event = match event {
+            Some(event) => event?,
+            None => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+        };
+
+        let Some(dir) = self.watched.get(&event.wd) else {
+            return Ok(self.dirs[0].clone());
+        };
+        Ok(match event.name {
+            Some(name) => dir.join(name),
+            None => dir.clone(),
+        })
+    }
+
+    /// Watch the directories and all of their subdirectories.
+    ///
+    /// Adding a watch for an already watched directory is a no-op.
+    fn add_watches(&mut self) -> std::io::Result<()> {
+        let mut dirs = self.dirs.clone();
+
+        while let Some(dir) = dirs.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                // The directory might be gone by now.
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+
+            let watch = self.watches.add(&dir, MASK)?;
+
+            for entry in entries {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    dirs.push(entry.path());
+                }
+            }
+
+            self.watched.insert(watch, dir);
+        }
+
+        Ok(())
+    }
+}
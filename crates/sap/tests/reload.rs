@@ -0,0 +1,133 @@
+//! Test the reloading of the site.
+
+use tokio::io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _};
+
+const EXECUTABLE_PATH: &str = env!("CARGO_BIN_EXE_sap");
+
+const ADDR: &str = "127.0.0.1:18121";
+
+const DEV_ADDR: &str = "127.0.0.1:18122";
+
+/// Send the `GET` request to the given address and return the response.
+async fn get_from(addr: &str, path: &str) -> String {
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let req = format!("GET {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n");
+    stream.write_all(req.as_bytes()).await.unwrap();
+
+    let mut res = String::new();
+    stream.read_to_string(&mut res).await.unwrap();
+    res
+}
+
+/// Send the `GET` request and return the response.
+async fn get(path: &str) -> String {
+    get_from(ADDR, path).await
+}
+
+/// Read the output lines until one contains the given text.
+async fn wait_for(
+    stdout: &mut tokio::io::Lines<tokio::io::BufReader<tokio::process::ChildStdout>>,
+    text: &str,
+) {
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        while let Some(line) = stdout.next_line().await.unwrap() {
+            println!("{line}");
+            if line.contains(text) {
+                return;
+            }
+        }
+        panic!("the output ended before {text:?}");
+    })
+    .await
+    .unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn failed_reload_keeps_serving_ready() {
+    let root_dir = std::env::temp_dir().join(format!("sap-reload-{}", std::process::id()));
+    std::fs::create_dir_all(&root_dir).unwrap();
+    std::fs::write(
+        root_dir.join("index.html"),
+        "<html><body>root</body></html>",
+    )
+    .unwrap();
+    std::fs::write(root_dir.join("404.html"), "<html><body>404</body></html>").unwrap();
+
+    let mut child = tokio::process::Command::new(EXECUTABLE_PATH)
+        .env("ADDR", ADDR)
+        .env("ROOT_DIR", &root_dir)
+        .env("ERROR_PAGES", "404=/404.html")
+        .env("RUST_LOG", "info")
+        .stdout(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let mut stdout = tokio::io::BufReader::new(child.stdout.take().unwrap()).lines();
+
+    wait_for(&mut stdout, "Started Tcp listening on").await;
+    let readyz = get("/__sap/readyz").await;
+    assert!(readyz.starts_with("HTTP/1.1 200 "), "{readyz}");
+    assert!(!readyz.contains("reload: failed"), "{readyz}");
+
+    // The error page is required, so the reloading fails without it.
+    std::fs::remove_file(root_dir.join("404.html")).unwrap();
+    let status = std::process::Command::new("kill")
+        .arg("-HUP")
+        .arg(child.id().unwrap().to_string())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    wait_for(&mut stdout, "Reloading failed").await;
+
+    let readyz = get("/__sap/readyz").await;
+    assert!(readyz.starts_with("HTTP/1.1 200 "), "{readyz}");
+    assert!(readyz.ends_with("ok\nreload: failed\n"), "{readyz}");
+    assert!(get("/").await.starts_with("HTTP/1.1 200 "));
+
+    child.start_kill().unwrap();
+    std::fs::remove_dir_all(&root_dir).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn dev_mode_updates_changed_files() {
+    let root_dir = std::env::temp_dir().join(format!("sap-update-{}", std::process::id()));
+    std::fs::create_dir_all(&root_dir).unwrap();
+    std::fs::write(
+        root_dir.join("index.html"),
+        "<html><body>root</body></html>",
+    )
+    .unwrap();
+    std::fs::write(root_dir.join("app.js"), "console.log(1);").unwrap();
+
+    let mut child = tokio::process::Command::new(EXECUTABLE_PATH)
+        .arg("dev")
+        .env("ADDR", DEV_ADDR)
+        .env("ROOT_DIR", &root_dir)
+        .env("PRECOMPRESS", "gzip")
+        .env("RUST_LOG", "info")
+        .stdout(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let mut stdout = tokio::io::BufReader::new(child.stdout.take().unwrap()).lines();
+
+    wait_for(&mut stdout, "Started Tcp listening on").await;
+    assert!(get_from(DEV_ADDR, "/app.js")
+        .await
+        .ends_with("console.log(1);"));
+
+    // Only the changed file is loaded again, rather than the whole site.
+    std::fs::write(root_dir.join("app.js"), "console.log(2);").unwrap();
+    wait_for(&mut stdout, "Site updated").await;
+
+    assert!(get_from(DEV_ADDR, "/app.js")
+        .await
+        .ends_with("console.log(2);"));
+    let root = get_from(DEV_ADDR, "/").await;
+    assert!(root.starts_with("HTTP/1.1 200 "), "{root}");
+
+    child.start_kill().unwrap();
+    std::fs::remove_dir_all(&root_dir).unwrap();
+}
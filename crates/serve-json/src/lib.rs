@@ -0,0 +1,424 @@
+//! The `serve.json` configuration compatibility.
+//!
+//! Compiles the `rewrites`, `redirects`, `headers`, `cleanUrls` and `trailingSlash` settings
+//! of the [serve](https://github.com/vercel/serve-handler#options) configuration into
+//! the [`mem_server`] routing. The other settings, and the globs form of `cleanUrls`, are not
+//! supported, and are listed in [`Compiled::ignored`] so that they can be warned about.
+//!
+//! The `rewrites` and `redirects` sources support the `:name` placeholders and the `*` or `**`
+//! as the last segment, captured as `:0` or `:splat` for the destination. The `headers` sources
+//! are globs, with the `@(a|b)` alternatives.
+
+/// The name of the configuration file.
+pub const FILE_NAME: &str = "serve.json";
+
+/// An error that can occur while loading the configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The configuration is not valid JSON or has unexpected values.
+    #[error("parsing: {0}")]
+    Parse(serde_json::Error),
+
+    /// The rewrite or redirect rule is invalid.
+    #[error("rule for source {0:?}: {1}")]
+    Rule(String, mem_server::RedirectError),
+
+    /// The redirect type is invalid.
+    #[error("redirect for source {0:?}: invalid type {1}")]
+    RedirectType(String, u16),
+
+    /// The headers source is invalid.
+    #[error("headers for source {0:?}: {1}")]
+    HeadersSource(String, yaml_headers::rules::RuleError),
+
+    /// The header name is invalid.
+    #[error("headers for source {0:?}: invalid name {1:?}")]
+    HeaderName(String, String),
+
+    /// The header value is invalid.
+    #[error("headers for source {0:?}: invalid value for {1:?}")]
+    HeaderValue(String, String),
+}
+
+/// The configuration file contents.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    /// Whether to serve the `.html` files without the extension.
+    #[serde(default)]
+    pub clean_urls: CleanUrls,
+
+    /// Whether to add (`true`) or remove (`false`) the trailing slashes.
+    #[serde(default)]
+    pub trailing_slash: Option<bool>,
+
+    /// The rewrite rules.
+    #[serde(default)]
+    pub rewrites: Vec<Rewrite>,
+
+    /// The redirect rules.
+    #[serde(default)]
+    pub redirects: Vec<Redirect>,
+
+    /// The header rules.
+    #[serde(default)]
+    pub headers: Vec<Headers>,
+
+    /// The other settings, which are not supported.
+    #[serde(flatten)]
+    pub other: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+/// The `cleanUrls` setting.
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum CleanUrls {
+    /// Whether to serve all the `.html` files without the extension.
+    All(bool),
+
+    /// The globs of the `.html` files to serve without the extension, which is not supported.
+    Globs(Vec<String>),
+}
+
+impl Default for CleanUrls {
+    fn default() -> Self {
+        Self::All(false)
+    }
+}
+
+/// A rewrite rule.
+#[derive(Debug, serde::Deserialize)]
+pub struct Rewrite {
+    /// The source path pattern.
+    pub source: String,
+
+    /// The destination path.
+    pub destination: String,
+}
+
+/// A redirect rule.
+#[derive(Debug, serde::Deserialize)]
+pub struct Redirect {
+    /// The source path pattern.
+    pub source: String,
+
+    /// The destination location.
+    pub destination: String,
+
+    /// The redirect status.
+    #[serde(rename = "type", default = "default_redirect_type")]
+    pub redirect_type: u16,
+}
+
+/// The default redirect status.
+fn default_redirect_type() -> u16 {
+    301
+}
+
+/// A header rule.
+#[derive(Debug, serde::Deserialize)]
+pub struct Headers {
+    /// The source glob.
+    pub source: String,
+
+    /// The headers to set, or to remove when there is no value.
+    pub headers: Vec<Header>,
+}
+
+/// A single header of the header rule.
+#[derive(Debug, serde::Deserialize)]
+pub struct Header {
+    /// The header name.
+    pub key: String,
+
+    /// The header value.
+    pub value: Option<String>,
+}
+
+/// The configuration compiled for the [`mem_server`].
+#[derive(Debug, Default)]
+pub struct Compiled {
+    /// The header rules, to be matched against the routes and the file paths.
+    pub header_rules: yaml_headers::rules::Rules,
+
+    /// The redirect and rewrite rules.
+    pub redirects: mem_server::Redirects,
+
+    /// The trailing slash and clean URLs policy.
+    pub url_policy: mem_server::UrlPolicy,
+
+    /// The settings that were ignored, as not supported.
+    pub ignored: Vec<String>,
+}
+
+impl std::str::FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s).map_err(Error::Parse)
+    }
+}
+
+impl Config {
+    /// Compile the configuration.
+    pub fn compile(self) -> Result<Compiled, Error> {
+        let mut redirects = Vec::with_capacity(self.redirects.len() + self.rewrites.len());
+
+        // The redirects apply regardless of the files, like in the `serve`.
+        for redirect in self.redirects {
+            let status = http::StatusCode::from_u16(redirect.redirect_type)
+                .ok()
+                .filter(|status| mem_server::is_redirect_status(*status))
+                .ok_or_else(|| {
+                    Error::RedirectType(redirect.source.clone(), redirect.redirect_type)
+                })?;
+            redirects.push(rule(&redirect.source, &redirect.destination, status, true)?);
+        }
+
+        // The rewrites only apply when there is no file, like in the `serve`.
+        for rewrite in self.rewrites {
+            redirects.push(rule(
+                &rewrite.source,
+                &rewrite.destination,
+                http::StatusCode::OK,
+                false,
+            )?);
+        }
+
+        let mut ignored: Vec<_> = self.other.into_keys().collect();
+        let clean_urls = match self.clean_urls {
+            CleanUrls::All(clean_urls) => clean_urls,
+            CleanUrls::Globs(_) => {
+                ignored.push("cleanUrls".to_owned());
+                false
+            }
+        };
+
+        let header_rules = self
+            .headers
+            .into_iter()
+            .map(header_rule)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Compiled {
+            header_rules: yaml_headers::rules::Rules(header_rules),
+            redirects: mem_server::Redirects(redirects),
+            url_policy: mem_server::UrlPolicy {
+                trailing_slash: match self.trailing_slash {
+                    None => mem_server::TrailingSlash::Keep,
+                    Some(true) => mem_server::TrailingSlash::Add,
+                    Some(false) => mem_server::TrailingSlash::Remove,
+                },
+                clean_urls,
+            },
+            ignored,
+        })
+    }
+}
+
+/// Build the redirect or rewrite rule.
+fn rule(
+    source: &str,
+    destination: &str,
+    status: http::StatusCode,
+    force: bool,
+) -> Result<mem_server::RedirectRule, Error> {
+    let from = slashed(source)
+        .split('/')
+        .map(|segment| if segment == "**" { "*" } else { segment })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let to = destination.replace(":0", ":splat");
+
+    from.parse()
+        .and_then(|from| mem_server::RedirectRule::new(from, to, status, force))
+        .map_err(|err| Error::Rule(source.to_owned(), err))
+}
+
+/// Build the header rule.
+fn header_rule(headers: Headers) -> Result<yaml_headers::rules::Rule, Error> {
+    let Headers { source, headers } = headers;
+
+    let matcher = yaml_headers::rules::Matcher::glob(&extglob_alternatives(&slashed(&source)))
+        .map_err(|err| Error::HeadersSource(source.clone(), err))?;
+
+    let mut rule = yaml_headers::rules::Rule {
+        matcher,
+        remove: Vec::new(),
+        set: http::HeaderMap::new(),
+        append: http::HeaderMap::new(),
+    };
+
+    for Header { key, value } in headers {
+        let name = http::HeaderName::try_from(key.as_str())
+            .map_err(|_| Error::HeaderName(source.clone(), key.clone()))?;
+
+        match value {
+            Some(value) => {
+                let value = http::HeaderValue::try_from(value)
+                    .map_err(|_| Error::HeaderValue(source.clone(), key))?;
+                rule.set.insert(name, value);
+            }
+            None => rule.remove.push(name),
+        }
+    }
+
+    Ok(rule)
+}
+
+/// Add the leading slash to the source, like the `serve` does.
+fn slashed(source: &str) -> String {
+    if source.starts_with('/') {
+        source.to_owned()
+    } else {
+        format!("/{source}")
+    }
+}
+
+/// Turn the `@(a|b)` extglob alternatives into the `{a,b}` ones.
+fn extglob_alternatives(glob: &str) -> String {
+    let mut result = String::with_capacity(glob.len());
+    let mut rest = glob;
+
+    while let Some(idx) = rest.find("@(") {
+        result.push_str(&rest[..idx]);
+        rest = &rest[idx + 2..];
+
+        let Some(end) = rest.find(')') else {
+            result.push_str("@(");
+            break;
+        };
+
+        result.push('{');
+        result.push_str(&rest[..end].replace('|', ","));
+        result.push('}');
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "public": "dist",
+        "cleanUrls": true,
+        "trailingSlash": false,
+        "rewrites": [
+            { "source": "app/**", "destination": "/app/index.html" },
+            { "source": "/projects/:id/edit", "destination": "/edit-project-:id.html" }
+        ],
+        "redirects": [
+            { "source": "/from", "destination": "/to" },
+            { "source": "/old/**", "destination": "/new/:0", "type": 302 }
+        ],
+        "headers": [
+            {
+                "source": "**/*.@(jpg|jpeg|gif|png)",
+                "headers": [
+                    { "key": "Cache-Control", "value": "max-age=7200" },
+                    { "key": "X-Powered-By" }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn compiling() {
+        let compiled = Config::from_str(SAMPLE).unwrap().compile().unwrap();
+
+        assert_eq!(
+            compiled.url_policy.trailing_slash,
+            mem_server::TrailingSlash::Remove
+        );
+        assert!(compiled.url_policy.clean_urls);
+        assert_eq!(compiled.ignored, ["public"]);
+
+        let cases: &[(&str, Option<mem_server::RedirectAction>)] = &[
+            (
+                "/from",
+                Some(mem_server::RedirectAction::Redirect(
+                    http::StatusCode::MOVED_PERMANENTLY,
+                    http::HeaderValue::from_static("/to"),
+                )),
+            ),
+            (
+                "/old/a/b",
+                Some(mem_server::RedirectAction::Redirect(
+                    http::StatusCode::FOUND,
+                    http::HeaderValue::from_static("/new/a/b"),
+                )),
+            ),
+            (
+                "/app/settings",
+                Some(mem_server::RedirectAction::Rewrite(
+                    http::StatusCode::OK,
+                    "/app/index.html".into(),
+                )),
+            ),
+            (
+                "/projects/1/edit",
+                Some(mem_server::RedirectAction::Rewrite(
+                    http::StatusCode::OK,
+                    "/edit-project-1.html".into(),
+                )),
+            ),
+            ("/other", None),
+        ];
+
+        for (path, expected) in cases {
+            assert_eq!(
+                &compiled.redirects.evaluate(path, None, false),
+                expected,
+                "{path}"
+            );
+        }
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-powered-by", http::HeaderValue::from_static("sap"));
+        compiled
+            .header_rules
+            .apply(&["/images/a.png"], &mut headers);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers["cache-control"], "max-age=7200");
+
+        let mut headers = http::HeaderMap::new();
+        compiled.header_rules.apply(&["/a.svg"], &mut headers);
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn clean_urls_globs() {
+        let compiled = Config::from_str(r#"{ "cleanUrls": ["/app/**"], "etag": true }"#)
+            .unwrap()
+            .compile()
+            .unwrap();
+
+        assert!(!compiled.url_policy.clean_urls);
+        assert_eq!(compiled.ignored, ["etag", "cleanUrls"]);
+    }
+
+    #[test]
+    fn invalid() {
+        let cases: &[&str] = &[
+            "not json",
+            r#"{ "cleanUrls": "yes" }"#,
+            r#"{ "redirects": [{ "source": "/a", "destination": "/b", "type": 200 }] }"#,
+            r#"{ "redirects": [{ "source": "/a", "destination": "/b", "type": 304 }] }"#,
+            r#"{ "rewrites": [{ "source": "/**/a", "destination": "/b" }] }"#,
+            r#"{ "rewrites": [{ "source": "/a", "destination": "https://example.com" }] }"#,
+            r#"{ "headers": [{ "source": "/a", "headers": [{ "key": "bad key", "value": "a" }] }] }"#,
+        ];
+
+        for sample in cases {
+            let result = Config::from_str(sample).and_then(Config::compile);
+            assert!(result.is_err(), "{sample}");
+        }
+    }
+}
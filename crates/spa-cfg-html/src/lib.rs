@@ -4,7 +4,7 @@
 use std::borrow::Cow;
 
 /// The enforcement mode to use when applying the configuration.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum TemplateTagPresence {
     /// Require the script tag containing the configuration template to be present.
     ///
@@ -16,7 +16,7 @@ pub enum TemplateTagPresence {
 }
 
 /// The HTML templating engine for the SPA configuration.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Engine {
     /// The prefix for the ENV vars to use.
     pub env_prefix: Cow<'static, str>,
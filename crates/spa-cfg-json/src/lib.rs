@@ -4,7 +4,7 @@
 use std::borrow::Cow;
 
 /// The HTML templating engine for the SPA configuration.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Engine {
     /// The prefix for the ENV vars to use.
     pub env_prefix: Cow<'static, str>,
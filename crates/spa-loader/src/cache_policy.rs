@@ -0,0 +1,158 @@
+//! Automatic caching policy for the content-hashed files.
+
+use std::str::FromStr;
+
+/// The default patterns for the content-hashed file names, like `main.3f9a1c2b.js`,
+/// `chunk-ABCD1234.css` and the Vite-style `index-BVGXrW3p.js`.
+///
+/// Besides the hex hashes, the base64url hashes of at least 8 characters are matched, as long
+/// as they have a digit or an uppercase letter among the first 8 characters, so that the plain
+/// words like in `jquery-datatables.js` are not mistaken for the hashes.
+pub const DEFAULT_HASHED_PATTERN: &str = concat!(
+    r"[.-][0-9a-fA-F]{8,}\. ",
+    r"[.-](?:",
+    r"[0-9A-Z][A-Za-z0-9_-]{7,}",
+    r"|[a-z_-]{1}[0-9A-Z][A-Za-z0-9_-]{6,}",
+    r"|[a-z_-]{2}[0-9A-Z][A-Za-z0-9_-]{5,}",
+    r"|[a-z_-]{3}[0-9A-Z][A-Za-z0-9_-]{4,}",
+    r"|[a-z_-]{4}[0-9A-Z][A-Za-z0-9_-]{3,}",
+    r"|[a-z_-]{5}[0-9A-Z][A-Za-z0-9_-]{2,}",
+    r"|[a-z_-]{6}[0-9A-Z][A-Za-z0-9_-]{1,}",
+    r"|[a-z_-]{7}[0-9A-Z][A-Za-z0-9_-]*",
+    r")\.",
+);
+
+/// The default `Cache-Control` for the content-hashed files.
+pub const DEFAULT_HASHED: &str = "public, max-age=31536000, immutable";
+
+/// The default `Cache-Control` for the HTML entry points and the other files.
+pub const DEFAULT_REVALIDATE: &str = "no-cache";
+
+/// The caching policy that sets the `Cache-Control` based on whether the file name
+/// is content-hashed.
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    /// The patterns matching the content-hashed file names.
+    ///
+    /// The patterns are matched against the file name only, not the whole path.
+    pub hashed_patterns: HashedPatterns,
+
+    /// The `Cache-Control` for the content-hashed files.
+    pub hashed: http::HeaderValue,
+
+    /// The `Cache-Control` for the HTML files and the files that are not content-hashed.
+    pub revalidate: http::HeaderValue,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            hashed_patterns: HashedPatterns::default(),
+            hashed: http::HeaderValue::from_static(DEFAULT_HASHED),
+            revalidate: http::HeaderValue::from_static(DEFAULT_REVALIDATE),
+        }
+    }
+}
+
+impl CachePolicy {
+    /// Pick the `Cache-Control` for the file with the given name and content type.
+    pub fn cache_control(
+        &self,
+        file_name: &str,
+        content_type: Option<&http::HeaderValue>,
+    ) -> &http::HeaderValue {
+        let is_html = content_type.is_some_and(|content_type| {
+            content_type
+                .as_bytes()
+                .split(|byte| *byte == b';')
+                .next()
+                .unwrap_or_default()
+                .trim_ascii()
+                .eq_ignore_ascii_case(b"text/html")
+        });
+
+        if !is_html && self.hashed_patterns.is_match(file_name) {
+            &self.hashed
+        } else {
+            &self.revalidate
+        }
+    }
+}
+
+/// A whitespace-separated list of the regex patterns matching the content-hashed file names.
+#[derive(Debug, Clone)]
+pub struct HashedPatterns(pub Vec<regex::Regex>);
+
+impl Default for HashedPatterns {
+    fn default() -> Self {
+        DEFAULT_HASHED_PATTERN.parse().unwrap() // the default patterns are valid
+    }
+}
+
+impl HashedPatterns {
+    /// Check whether any of the patterns matches the file name.
+    pub fn is_match(&self, file_name: &str) -> bool {
+        self.0.iter().any(|pattern| pattern.is_match(file_name))
+    }
+}
+
+impl FromStr for HashedPatterns {
+    type Err = regex::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let patterns = s
+            .split_whitespace()
+            .map(regex::Regex::new)
+            .collect::<Result<_, _>>()?;
+        Ok(Self(patterns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_control() {
+        let policy = CachePolicy::default();
+
+        let js = http::HeaderValue::from_static("text/javascript");
+        let html = http::HeaderValue::from_static("text/html; charset=utf-8");
+
+        let cases: &[(&str, Option<&http::HeaderValue>, &str)] = &[
+            ("main.3f9a1c2b.js", Some(&js), DEFAULT_HASHED),
+            ("chunk-ABCD1234.css", None, DEFAULT_HASHED),
+            ("main.3f9a1c2b.js.br", None, DEFAULT_HASHED),
+            ("index-BVGXrW3p.js", Some(&js), DEFAULT_HASHED),
+            ("vendor-dkwq_9Tq.js", Some(&js), DEFAULT_HASHED),
+            ("chunk-abcdefg1.js", Some(&js), DEFAULT_HASHED),
+            ("react-router-dom.js", Some(&js), DEFAULT_REVALIDATE),
+            ("app-v2.js", Some(&js), DEFAULT_REVALIDATE),
+            ("bootstrap-5.3.3.js", Some(&js), DEFAULT_REVALIDATE),
+            ("main.js", Some(&js), DEFAULT_REVALIDATE),
+            ("jquery-datatables.js", Some(&js), DEFAULT_REVALIDATE),
+            ("deadbeef.js", Some(&js), DEFAULT_REVALIDATE),
+            ("index.html", Some(&html), DEFAULT_REVALIDATE),
+            ("page.3f9a1c2b.html", Some(&html), DEFAULT_REVALIDATE),
+        ];
+
+        for (file_name, content_type, expected) in cases {
+            assert_eq!(
+                policy.cache_control(file_name, *content_type),
+                expected,
+                "{file_name}"
+            );
+        }
+    }
+
+    #[test]
+    fn patterns() {
+        let patterns = HashedPatterns::from_str(r"-[A-Za-z0-9_]{8}\.  [.-][0-9a-f]{8,}\.").unwrap();
+        assert_eq!(patterns.0.len(), 2);
+        assert!(patterns.is_match("index-BVGXrW3p.js"));
+        assert!(patterns.is_match("main.3f9a1c2b.js"));
+        assert!(!patterns.is_match("main.js"));
+
+        assert!(HashedPatterns::from_str("(").is_err());
+    }
+}
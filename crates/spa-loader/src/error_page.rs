@@ -0,0 +1,100 @@
+//! Error pages configuration.
+
+use std::str::FromStr;
+
+/// An error page: the route to serve the content of for the responses with the given status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorPage {
+    /// The status of the responses to use the page for.
+    pub status: http::StatusCode,
+
+    /// The route to take the page from, like `/404.html`.
+    pub route: String,
+}
+
+/// An error that can occur while parsing the error pages list.
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    /// The item is not in the `status=route` form.
+    #[error("expected the status=route form, got {0:?}")]
+    InvalidItem(String),
+
+    /// The status code is invalid.
+    #[error("invalid status code {0:?}: {1}")]
+    InvalidStatus(String, http::status::InvalidStatusCode),
+
+    /// The status code is not an error status code.
+    #[error("status code {0} is not an error")]
+    NotAnError(http::StatusCode),
+
+    /// The route does not start with a slash.
+    #[error("route {0:?} must start with a slash")]
+    InvalidRoute(String),
+}
+
+/// A comma-separated list of the error pages in the `status=route` form,
+/// like `404=/404.html,405=/405.html`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct List(pub Vec<ErrorPage>);
+
+impl FromStr for List {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let list = s
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                let (status, route) = item
+                    .split_once('=')
+                    .ok_or_else(|| ParseError::InvalidItem(item.to_owned()))?;
+                let (status, route) = (status.trim(), route.trim());
+
+                let status = http::StatusCode::from_str(status)
+                    .map_err(|err| ParseError::InvalidStatus(status.to_owned(), err))?;
+                if !status.is_client_error() && !status.is_server_error() {
+                    return Err(ParseError::NotAnError(status));
+                }
+
+                if !route.starts_with('/') {
+                    return Err(ParseError::InvalidRoute(route.to_owned()));
+                }
+
+                Ok(ErrorPage {
+                    status,
+                    route: route.to_owned(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(list))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        assert_eq!(List::from_str("").unwrap(), List(vec![]));
+        assert_eq!(
+            List::from_str("404=/404.html, 405 = /errors/405.html").unwrap(),
+            List(vec![
+                ErrorPage {
+                    status: http::StatusCode::NOT_FOUND,
+                    route: "/404.html".into()
+                },
+                ErrorPage {
+                    status: http::StatusCode::METHOD_NOT_ALLOWED,
+                    route: "/errors/405.html".into()
+                },
+            ])
+        );
+
+        assert!(List::from_str("/404.html").is_err());
+        assert!(List::from_str("abc=/404.html").is_err());
+        assert!(List::from_str("200=/index.html").is_err());
+        assert!(List::from_str("404=404.html").is_err());
+    }
+}
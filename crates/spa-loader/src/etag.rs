@@ -0,0 +1,25 @@
+//! Entity tags computation.
+
+use std::fmt::Write as _;
+
+use sha2::Digest as _;
+
+/// The amount of the digest bytes to use in the entity tag.
+const DIGEST_BYTES: usize = 16;
+
+/// Compute a strong entity tag for the given response body.
+///
+/// The tag is derived from the body contents only, so the same content always produces the same
+/// tag across the restarts and replicas.
+pub fn compute(body: &[u8]) -> http::HeaderValue {
+    let digest = sha2::Sha256::digest(body);
+
+    let mut etag = String::with_capacity(DIGEST_BYTES * 2 + 2);
+    etag.push('"');
+    for byte in &digest[..DIGEST_BYTES] {
+        write!(etag, "{byte:02x}").unwrap(); // string write never fails
+    }
+    etag.push('"');
+
+    http::HeaderValue::try_from(etag).unwrap() // hex digits and quotes are always valid
+}
@@ -1,7 +1,11 @@
 //! An opinionated SPA (Single Page App) loader.
 
+pub mod cache_policy;
+pub mod error_page;
 pub mod route_from_file_path;
 
+mod etag;
+
 use std::path::PathBuf;
 
 /// The type used for the file size operations.
@@ -42,6 +46,26 @@ pub enum LoadError {
     #[error("adding file {0:?} resulted in the route duplucate {1:?}")]
     DuplicateRoute(PathBuf, String),
 
+    /// Unable to compress the response body.
+    #[error("compressing the file {0:?} with {1:?}: {2}")]
+    Compression(PathBuf, content_encoding::Encoding, std::io::Error),
+
+    /// The route configured as the error page was not loaded.
+    #[error("the error page route {1:?} for the status {0} was not found")]
+    ErrorPageNotFound(http::StatusCode, String),
+
+    /// Unable to read a control file.
+    #[error("reading the control file {0:?}: {1}")]
+    ReadingControlFile(PathBuf, std::io::Error),
+
+    /// Unable to parse the `_headers` file.
+    #[error("parsing the headers file {0:?}: {1}")]
+    NetlifyHeaders(PathBuf, netlify_files::headers::Error),
+
+    /// Unable to load the `serve.json` file.
+    #[error("loading the serve configuration file {0:?}: {1}")]
+    ServeJson(PathBuf, serve_json::Error),
+
     /// The templating for a given file/route has failed.
     #[error("applying the templating for file {0:?} (route {1:?}): {2}")]
     Templating(PathBuf, String, TemplatingError),
@@ -59,7 +83,7 @@ pub enum TemplatingError {
 }
 
 /// An opinionated SPA code loader.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Loader {
     /// The max size of the file to load.
     ///
@@ -73,11 +97,42 @@ pub struct Loader {
     /// Will be used as a prefix to strip from the file paths before converting them to routes.
     pub root_dir: PathBuf,
 
+    /// The path prefix to mount the routes under, like `/admin`, without the trailing slash.
+    ///
+    /// Empty to mount the routes at the root. The root page used as not found is served for
+    /// the unknown paths under the prefix, and the paths in the control files and the error
+    /// pages are relative to the prefix.
+    pub mount_prefix: String,
+
+    /// Whether to point the `<base href>` of the root page at the mount prefix, injecting
+    /// the `base` element if the page has none.
+    ///
+    /// Lets the SPA built for the root resolve its relative URLs under the prefix.
+    pub base_href: bool,
+
     /// Whether to use the root page as not found.
     ///
     /// Useful for the apps with dynamic routing.
     pub root_as_not_found: bool,
 
+    /// The status code to respond with when the root page is used as not found.
+    ///
+    /// Serving the fallback with `404 Not Found` lets the crawlers and monitoring tell
+    /// the missing pages from the real ones, while the browsers still render the app.
+    pub not_found_status: http::StatusCode,
+
+    /// Headers to override for the responses when the root page is used as not found.
+    ///
+    /// Applied on top of the root page headers, replacing the values with the same names.
+    pub not_found_headers: http::HeaderMap,
+
+    /// The pages to respond with for the given error statuses.
+    ///
+    /// The `404 Not Found` page is served for the requests that do not match any route
+    /// and are not handled by the root page as not found, which takes precedence for
+    /// the navigation requests when enabled.
+    pub error_pages: Vec<error_page::ErrorPage>,
+
     /// Templating configuration for the root route.
     ///
     /// The current implementation only does tempating for the root route and only
@@ -90,151 +145,819 @@ pub struct Loader {
     /// using the [`spa_cfg_json`] facilities.
     pub config_json_templating: Option<spa_cfg_json::Engine>,
 
+    /// The inline script to append to the body of every HTML page, like the live reload client.
+    ///
+    /// The precompressed siblings of the HTML pages are ignored when set.
+    pub html_script: Option<String>,
+
     /// Headers to set for responses.
     pub headers: http::HeaderMap,
+
+    /// The per-path header rules, applied on top of the [`Self::headers`].
+    ///
+    /// The rules are matched against both the route and the file path relative to the root
+    /// dir with the leading slash, so that the `/` route is matched by the `/index.html` too.
+    pub header_rules: yaml_headers::rules::Rules,
+
+    /// The automatic caching policy for the content-hashed files.
+    ///
+    /// When set, every response gets the `Cache-Control` picked by the policy, overriding
+    /// the [`Self::headers`], but not the [`Self::header_rules`].
+    pub cache_policy: Option<cache_policy::CachePolicy>,
+
+    /// The encodings to precompress the responses with.
+    ///
+    /// Only the responses with the compressible content types are precompressed, and only
+    /// the encoded variants that are smaller than the original are kept.
+    pub precompress: Vec<content_encoding::Encoding>,
+
+    /// Whether to treat the `_headers` and `_redirects` files in the root dir as the Netlify-style
+    /// control files rather than serving them as routes.
+    ///
+    /// The header blocks are applied on top of all the other headers.
+    pub netlify_files: bool,
+
+    /// Whether to treat the `serve.json` file in the root dir as the `serve` configuration
+    /// rather than serving it as a route.
+    ///
+    /// The headers are applied on top of all the other headers, and the redirects are evaluated
+    /// before the Netlify-style ones.
+    pub serve_json: bool,
+
+    /// Whether the `trailingSlash` and `cleanUrls` of the `serve.json` set the URL policy of
+    /// the server the routes are loaded into.
+    ///
+    /// The URL policy applies to the whole server, so the mounts sharing the server with
+    /// another SPA leave it to that SPA and ignore theirs.
+    pub serve_json_url_policy: bool,
+
+    /// Whether to treat the `.br`, `.gz` and `.zst` files placed next to the other files as their
+    /// precompressed variants instead of serving them as the separate routes.
+    ///
+    /// The files with no original file next to them are served as the regular routes.
+    pub precompressed_siblings: bool,
 }
 
 impl Loader {
     /// Load the SPA code from the filesystem and prepare it to be served.
     pub async fn load(&self) -> Result<mem_server::MemServer, LoadError> {
         let mut server = mem_server::MemServer::default();
+        self.load_into(&mut server).await?;
+        Ok(server)
+    }
+
+    /// Load the SPA code from the filesystem into the given server, alongside the routes
+    /// it already has.
+    pub async fn load_into(&self, server: &mut mem_server::MemServer) -> Result<(), LoadError> {
         let mut content_type_detector = content_type::Detector::default();
         self.populate_from(
             vec![self.root_dir.to_path_buf()],
-            &mut server,
+            server,
+            &mut content_type_detector,
+        )
+        .await
+    }
+
+    /// Load the changed files of the SPA again into the given server it is loaded in.
+    ///
+    /// Only the changed files are loaded, along with their precompressed siblings, so only
+    /// they are precompressed again. The header control files are loaded to apply their headers
+    /// to the changed files, while the redirects and the URL policy the server has are kept.
+    ///
+    /// Returns `false` when the changes can not be applied this way, like the changes to
+    /// the control files, the dirs, or the removed files, so the SPA has to be loaded again
+    /// in full. The server may be partially updated then.
+    pub async fn update(
+        &self,
+        server: &mut mem_server::MemServer,
+        changed: &[PathBuf],
+    ) -> Result<bool, LoadError> {
+        let mut paths = std::collections::BTreeSet::new();
+        let mut stale_routes = Vec::new();
+        for path in changed {
+            let route_path = match path.strip_prefix(&self.root_dir) {
+                Ok(stripped) => stripped,
+                Err(err) => return Err(LoadError::RootDirPrefixStrip(path.clone(), err)),
+            };
+            match tokio::fs::metadata(path).await {
+                Ok(metadata) if metadata.is_file() => {}
+                Ok(_) => return Ok(false),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+                Err(err) => return Err(LoadError::ReadingDirEntryMetadata(path.clone(), err)),
+            }
+            if self.is_control_file(route_path) {
+                return Ok(false);
+            }
+
+            // The file is loaded again along with all the precompressed variants of its content.
+            let mut group = vec![route_path.to_path_buf()];
+            if self.precompressed_siblings {
+                let base_route_path = match PrecompressedSibling::detect(route_path)? {
+                    Some(_) => route_path.with_extension(""),
+                    None => route_path.to_path_buf(),
+                };
+                group = std::iter::once(base_route_path.clone())
+                    .chain(PrecompressedSibling::EXTENSIONS.iter().map(|ext| {
+                        let mut sibling_path = base_route_path.clone().into_os_string();
+                        sibling_path.push(".");
+                        sibling_path.push(ext);
+                        PathBuf::from(sibling_path)
+                    }))
+                    .collect();
+            }
+            for route_path in group {
+                let route = route_from_file_path::convert(&route_path)
+                    .map_err(|err| LoadError::RouteConversion(route_path.clone(), err))?;
+                stale_routes.push(self.mounted(&route));
+                let path = self.root_dir.join(route_path);
+                if tokio::fs::try_exists(&path).await.unwrap_or_default() {
+                    paths.insert(path);
+                }
+            }
+        }
+
+        for route in &stale_routes {
+            server.routes.remove(route);
+        }
+
+        let control_files = [
+            (self.serve_json, serve_json::FILE_NAME),
+            (self.netlify_files, netlify_files::HEADERS_FILE),
+        ];
+        for (enabled, file_name) in control_files {
+            let path = self.root_dir.join(file_name);
+            if enabled && tokio::fs::try_exists(&path).await.unwrap_or_default() {
+                paths.insert(path);
+            }
+        }
+
+        let redirects = server.redirects.clone();
+        let url_policy = server.url_policy.clone();
+        let mut content_type_detector = content_type::Detector::default();
+        self.populate_from(
+            paths.into_iter().collect(),
+            server,
             &mut content_type_detector,
         )
         .await?;
-        Ok(server)
+        server.redirects = redirects;
+        server.url_policy = url_policy;
+
+        Ok(true)
     }
 
-    /// Populate the given server with the SPA code from the filesystem.
+    /// Populate the given server with the SPA code from the given dirs and files of
+    /// the filesystem.
+    ///
+    /// The dirs are visited recursively, and the files are loaded as if they were found in
+    /// the dirs.
     pub async fn populate_from(
         &self,
-        mut dirs: Vec<PathBuf>,
+        paths: Vec<PathBuf>,
         server: &mut mem_server::MemServer,
         content_type_detector: &mut content_type::Detector,
     ) -> Result<(), LoadError> {
-        loop {
-            let Some(dir) = dirs.pop() else {
-                tracing::debug!(message = "All dirs visited");
-                break;
-            };
-
-            tracing::debug!(message = "Visiting dir", ?dir);
-
-            let mut read_dir = tokio::fs::read_dir(&dir)
+        let mut siblings = Vec::new();
+        let mut netlify_headers = None;
+        let mut serve_json = None;
+        let mut netlify_redirects = Vec::new();
+        let mut loaded = Vec::new();
+
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+        for path in paths {
+            let metadata = tokio::fs::metadata(&path)
                 .await
-                .map_err(|err| LoadError::ReadingDir(dir.to_path_buf(), err))?;
+                .map_err(|err| LoadError::ReadingDirEntryMetadata(path.to_path_buf(), err))?;
+            if metadata.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push((path, metadata));
+            }
+        }
 
-            loop {
-                let maybe_dir_entry = read_dir
-                    .next_entry()
-                    .await
-                    .map_err(|err| LoadError::ReadingDirEntry(dir.to_path_buf(), err))?;
-                let Some(dir_entry) = maybe_dir_entry else {
-                    tracing::debug!(message = "All entries in dir process", ?dir);
+        loop {
+            let Some((dir_entry_path, metadata)) = files.pop() else {
+                let Some(dir) = dirs.pop() else {
+                    tracing::debug!(message = "All dirs visited");
                     break;
                 };
 
-                let dir_entry_path = dir_entry.path();
-                tracing::debug!(message = "Processing dir entry", ?dir_entry_path);
+                tracing::debug!(message = "Visiting dir", ?dir);
+
+                let mut read_dir = tokio::fs::read_dir(&dir)
+                    .await
+                    .map_err(|err| LoadError::ReadingDir(dir.to_path_buf(), err))?;
+
+                loop {
+                    let maybe_dir_entry = read_dir
+                        .next_entry()
+                        .await
+                        .map_err(|err| LoadError::ReadingDirEntry(dir.to_path_buf(), err))?;
+                    let Some(dir_entry) = maybe_dir_entry else {
+                        tracing::debug!(message = "All entries in dir process", ?dir);
+                        break;
+                    };
+
+                    let dir_entry_path = dir_entry.path();
+                    tracing::debug!(message = "Processing dir entry", ?dir_entry_path);
+
+                    let metadata = dir_entry.metadata().await.map_err(|err| {
+                        LoadError::ReadingDirEntryMetadata(dir_entry_path.to_path_buf(), err)
+                    })?;
+
+                    if metadata.is_dir() {
+                        tracing::debug!(
+                            message = "Queueing another dir for visiting",
+                            ?dir_entry_path
+                        );
+                        dirs.push(dir_entry_path);
+                    } else {
+                        files.push((dir_entry_path, metadata));
+                    }
+                }
+                continue;
+            };
+
+            let route_path = match dir_entry_path.strip_prefix(&self.root_dir) {
+                Ok(stripped) => stripped,
+                Err(err) => return Err(LoadError::RootDirPrefixStrip(dir_entry_path, err)),
+            };
 
-                let metadata = dir_entry.metadata().await.map_err(|err| {
-                    LoadError::ReadingDirEntryMetadata(dir_entry_path.to_path_buf(), err)
-                })?;
+            if self.serve_json && route_path == std::path::Path::new(serve_json::FILE_NAME) {
+                let data = self.read_control_file(&dir_entry_path).await?;
+                let compiled = data
+                    .parse::<serve_json::Config>()
+                    .and_then(serve_json::Config::compile)
+                    .map_err(|err| LoadError::ServeJson(dir_entry_path, err))?;
+                tracing::info!(
+                    message = "Loaded the serve configuration file",
+                    headers = compiled.header_rules.0.len(),
+                    redirects = compiled.redirects.0.len(),
+                    url_policy = ?compiled.url_policy,
+                );
+                for setting in &compiled.ignored {
+                    tracing::warn!(message = "Ignoring the unsupported serve configuration setting", %setting);
+                }
+                serve_json = Some(compiled);
+                continue;
+            }
 
-                if metadata.is_dir() {
-                    tracing::debug!(
-                        message = "Queueing another dir for visiting",
-                        ?dir_entry_path
+            if self.netlify_files {
+                if route_path == std::path::Path::new(netlify_files::HEADERS_FILE) {
+                    let data = self.read_control_file(&dir_entry_path).await?;
+                    let rules = netlify_files::headers::parse(&data)
+                        .map_err(|err| LoadError::NetlifyHeaders(dir_entry_path, err))?;
+                    tracing::info!(message = "Loaded the headers file", rules = rules.0.len());
+                    netlify_headers = Some(rules);
+                    continue;
+                }
+
+                if route_path == std::path::Path::new(netlify_files::REDIRECTS_FILE) {
+                    let data = self.read_control_file(&dir_entry_path).await?;
+                    let parsed = netlify_files::redirects::parse(&data);
+                    for err in &parsed.skipped {
+                        tracing::warn!(message = "Skipping the redirects file line", ?dir_entry_path, %err);
+                    }
+                    tracing::info!(
+                        message = "Loaded the redirects file",
+                        rules = parsed.redirects.0.len(),
+                        skipped = parsed.skipped.len()
                     );
-                    dirs.push(dir_entry_path);
+                    netlify_redirects = parsed.redirects.0;
                     continue;
                 }
+            }
 
-                let route_path = match dir_entry_path.strip_prefix(&self.root_dir) {
-                    Ok(stripped) => stripped,
-                    Err(err) => return Err(LoadError::RootDirPrefixStrip(dir_entry_path, err)),
-                };
+            if self.precompressed_siblings {
+                if let Some(sibling) = PrecompressedSibling::detect(route_path)? {
+                    tracing::debug!(message = "Deferring the precompressed sibling", ?dir_entry_path, base_route = %sibling.base_route);
+                    let file_path = file_path(route_path);
+                    siblings.push((dir_entry_path, sibling, file_path));
+                    continue;
+                }
+            }
 
-                let route = route_from_file_path::convert(route_path)
-                    .map_err(|err| LoadError::RouteConversion(route_path.to_path_buf(), err))?;
+            let route = route_from_file_path::convert(route_path)
+                .map_err(|err| LoadError::RouteConversion(route_path.to_path_buf(), err))?;
 
-                let route_entry = match server.routes.entry(route) {
-                    std::collections::hash_map::Entry::Occupied(entry) => {
-                        return Err(LoadError::DuplicateRoute(
-                            dir_entry_path,
-                            entry.key().clone(),
-                        ));
-                    }
-                    std::collections::hash_map::Entry::Vacant(entry) => entry,
-                };
+            loaded.push((route.clone(), file_path(route_path)));
 
-                let route = route_entry.key();
+            self.add_route(
+                server,
+                route,
+                dir_entry_path,
+                &metadata,
+                content_type_detector,
+            )
+            .await?;
+        }
 
-                tracing::debug!(message = "Loading body from the route", %route, ?dir_entry_path);
+        for (dir_entry_path, sibling, file_path) in siblings {
+            let orphan = self
+                .add_precompressed_sibling(server, dir_entry_path, sibling, content_type_detector)
+                .await?;
+            if let Some(route) = orphan {
+                loaded.push((route, file_path));
+            }
+        }
 
-                let mut body = match tokio::fs::read(&dir_entry_path).await {
-                    Ok(data) => data,
-                    Err(err) => return Err(LoadError::ReadingBody(dir_entry_path, err)),
+        // The control files headers apply on top of all the other headers.
+        if serve_json.is_some() || netlify_headers.is_some() {
+            for (route, file_path) in &loaded {
+                let Some(prepared) = server.routes.get_mut(&self.mounted(route)) else {
+                    continue;
                 };
-
-                let file_size: FileSize = body.len().try_into().unwrap();
-                if file_size > self.max_file_size {
-                    return Err(LoadError::MaxFileSizeExceeded(dir_entry_path, file_size));
+                for res in prepared.responses_mut() {
+                    if let Some(compiled) = &serve_json {
+                        // The `serve.json` header sources are matched against the file paths.
+                        compiled
+                            .header_rules
+                            .apply(&[route, file_path], res.headers_mut());
+                    }
+                    if let Some(rules) = &netlify_headers {
+                        rules.apply(&[route], res.headers_mut());
+                    }
                 }
+            }
+        }
 
-                if route == "/" {
-                    if let Some(templating_engine) = &self.root_templating {
-                        if let Err(err) = templating_engine.apply(&mut body) {
-                            return Err(LoadError::Templating(
-                                dir_entry_path,
-                                route.into(),
-                                TemplatingError::Html(err),
-                            ));
-                        };
-                        tracing::info!(message = "Successfully applied HTML templating", %route, ?dir_entry_path);
-                    }
+        let mut redirects = Vec::new();
+        if let Some(compiled) = serve_json {
+            redirects.extend(compiled.redirects.0);
+            if self.serve_json_url_policy {
+                server.url_policy = compiled.url_policy;
+            } else if compiled.url_policy != mem_server::UrlPolicy::default() {
+                tracing::warn!(message = "Ignoring the serve configuration URL policy of the mount", mount_prefix = %self.mount_prefix, url_policy = ?compiled.url_policy);
+            }
+        }
+        redirects.extend(netlify_redirects);
+        for mut rule in redirects {
+            rule.mount(&self.mount_prefix);
+            server.redirects.0.push(rule);
+        }
+
+        // The mounted root is also served with the trailing slash, where its base href points.
+        if !self.mount_prefix.is_empty() {
+            if let Some(root_route) = server.routes.get(&self.mounted("/")) {
+                let root_route = root_route.clone();
+                server
+                    .routes
+                    .insert(format!("{}/", self.mount_prefix), root_route);
+            }
+        }
+
+        // Use the root response for not found if requested.
+        if self.root_as_not_found {
+            if let Some(root_route) = server.routes.get(&self.mounted("/")) {
+                tracing::info!(
+                    message = "Using root as not found route",
+                    status = %self.not_found_status
+                );
+                let mut fallback = root_route.clone();
+                for res in fallback.responses_mut() {
+                    *res.status_mut() = self.not_found_status;
+                    res.headers_mut().extend(self.not_found_headers.clone());
                 }
-                if route == "/config.json" {
-                    if let Some(templating_engine) = &self.config_json_templating {
-                        if let Err(err) = templating_engine.apply(&mut body) {
-                            return Err(LoadError::Templating(
-                                dir_entry_path,
-                                route.into(),
-                                TemplatingError::Json(err),
-                            ));
-                        };
-                        tracing::info!(message = "Successfully applied JSON templating", %route, ?dir_entry_path);
-                    }
+                if self.mount_prefix.is_empty() {
+                    server.routes.set_fallback(Some(fallback));
+                } else {
+                    server.routes.insert_prefix(&self.mount_prefix, fallback);
                 }
+            }
+        }
 
-                let maybe_content_type = content_type_detector.detect(route, body.as_slice());
+        for error_page in &self.error_pages {
+            let Some(route) = server.routes.get(&self.mounted(&error_page.route)) else {
+                return Err(LoadError::ErrorPageNotFound(
+                    error_page.status,
+                    error_page.route.clone(),
+                ));
+            };
 
-                tracing::info!(message = "Adding route", %route, %file_size, ?maybe_content_type);
+            tracing::info!(message = "Using error page", status = %error_page.status, route = %error_page.route);
 
-                let mut res = http::Response::new(body.into());
+            let mut page = route.clone();
+            for res in page.responses_mut() {
+                *res.status_mut() = error_page.status;
+            }
+            server.routes.set_error_page(error_page.status, page);
+        }
 
-                res.headers_mut().extend(self.headers.clone());
+        Ok(())
+    }
 
-                if let Some(content_type) = maybe_content_type {
-                    res.headers_mut()
-                        .insert(http::header::CONTENT_TYPE, content_type);
-                }
+    /// Load the file and add it to the server at the given route.
+    async fn add_route(
+        &self,
+        server: &mut mem_server::MemServer,
+        route: String,
+        dir_entry_path: PathBuf,
+        metadata: &std::fs::Metadata,
+        content_type_detector: &mut content_type::Detector,
+    ) -> Result<(), LoadError> {
+        if server.routes.contains(&self.mounted(&route)) {
+            return Err(LoadError::DuplicateRoute(
+                dir_entry_path,
+                self.mounted(&route),
+            ));
+        }
+
+        tracing::debug!(message = "Loading body from the route", %route, ?dir_entry_path);
+
+        let mut body = self.read_body(&dir_entry_path).await?;
+        let file_size = body.len();
+
+        let mut templated = false;
 
-                route_entry.insert(res);
+        if route == "/" {
+            if let Some(templating_engine) = &self.root_templating {
+                if let Err(err) = templating_engine.apply(&mut body) {
+                    return Err(LoadError::Templating(
+                        dir_entry_path,
+                        route,
+                        TemplatingError::Html(err),
+                    ));
+                };
+                tracing::info!(message = "Successfully applied HTML templating", %route, ?dir_entry_path);
+                templated = true;
+            }
+            if self.base_href {
+                let href = format!("{}/", self.mount_prefix);
+                body = html_templating::set_base_href(&body, &href);
+                tracing::info!(message = "Set the base href", %route, %href, ?dir_entry_path);
+                templated = true;
+            }
+        }
+        if route == "/config.json" {
+            if let Some(templating_engine) = &self.config_json_templating {
+                if let Err(err) = templating_engine.apply(&mut body) {
+                    return Err(LoadError::Templating(
+                        dir_entry_path,
+                        route,
+                        TemplatingError::Json(err),
+                    ));
+                };
+                tracing::info!(message = "Successfully applied JSON templating", %route, ?dir_entry_path);
+                templated = true;
             }
         }
 
-        // Use the root response for not found if requested.
-        if self.root_as_not_found {
-            if let Some(root_response) = server.routes.get("/") {
-                tracing::info!(message = "Using root as not found route");
-                server.not_found = Some(root_response.clone());
+        let maybe_content_type = content_type_detector.detect(&route, body.as_slice());
+
+        if let Some(script) = &self.html_script {
+            if is_html(maybe_content_type.as_ref()) {
+                body = html_templating::append_script(&body, script);
+                templated = true;
             }
         }
 
+        tracing::info!(message = "Adding route", %route, %file_size, ?maybe_content_type);
+
+        let etag = etag::compute(&body);
+
+        // The templated body depends on the env rather than on the file, so the file
+        // modification time does not describe it.
+        let last_modified = if templated {
+            None
+        } else {
+            metadata.modified().ok()
+        };
+
+        let mut res = http::Response::new(body.into());
+
+        res.headers_mut().extend(self.headers.clone());
+
+        res.headers_mut().insert(http::header::ETAG, etag);
+
+        if let Some(last_modified) = last_modified {
+            let last_modified = httpdate::fmt_http_date(last_modified);
+            res.headers_mut().insert(
+                http::header::LAST_MODIFIED,
+                http::HeaderValue::try_from(last_modified).unwrap(), // http dates are valid header values
+            );
+        }
+
+        let is_compressible = maybe_content_type.as_ref().is_some_and(|content_type| {
+            content_type
+                .to_str()
+                .is_ok_and(precompress::is_compressible)
+        });
+
+        if let Some(cache_policy) = &self.cache_policy {
+            let file_name = dir_entry_path
+                .file_name()
+                .and_then(std::ffi::OsStr::to_str)
+                .unwrap_or_default();
+            let cache_control = cache_policy.cache_control(file_name, maybe_content_type.as_ref());
+            res.headers_mut()
+                .insert(http::header::CACHE_CONTROL, cache_control.clone());
+        }
+
+        if let Some(content_type) = maybe_content_type {
+            res.headers_mut()
+                .insert(http::header::CONTENT_TYPE, content_type);
+        }
+
+        self.apply_header_rules(&route, &dir_entry_path, res.headers_mut());
+
+        let mut prepared = mem_server::Route::from(res);
+
+        if is_compressible {
+            self.precompress(&mut prepared, &dir_entry_path).await?;
+        }
+
+        server.routes.insert(self.mounted(&route), prepared);
+
         Ok(())
     }
+
+    /// Attach the precompressed sibling file to its base route as an encoded variant, or add it
+    /// as a regular route if there is no base route.
+    ///
+    /// Returns the route the file was added at as a regular route.
+    async fn add_precompressed_sibling(
+        &self,
+        server: &mut mem_server::MemServer,
+        dir_entry_path: PathBuf,
+        sibling: PrecompressedSibling,
+        content_type_detector: &mut content_type::Detector,
+    ) -> Result<Option<String>, LoadError> {
+        let PrecompressedSibling {
+            route,
+            base_route,
+            encoding,
+        } = sibling;
+
+        if self.is_templated(&base_route) {
+            tracing::warn!(message = "Ignoring the precompressed sibling of the templated route", route = %base_route, ?dir_entry_path);
+            return Ok(None);
+        }
+
+        let base_is_html = server
+            .routes
+            .get(&self.mounted(&base_route))
+            .is_some_and(|base| is_html(base.identity.headers().get(http::header::CONTENT_TYPE)));
+        if self.html_script.is_some() && base_is_html {
+            tracing::warn!(message = "Ignoring the precompressed sibling of the page with the script", route = %base_route, ?dir_entry_path);
+            return Ok(None);
+        }
+
+        let Some(base) = server.routes.get_mut(&self.mounted(&base_route)) else {
+            tracing::debug!(message = "No base route for the precompressed sibling, adding as is", %route, ?dir_entry_path);
+            let metadata = tokio::fs::metadata(&dir_entry_path).await.map_err(|err| {
+                LoadError::ReadingDirEntryMetadata(dir_entry_path.to_path_buf(), err)
+            })?;
+            self.add_route(
+                server,
+                route.clone(),
+                dir_entry_path,
+                &metadata,
+                content_type_detector,
+            )
+            .await?;
+            return Ok(Some(route));
+        };
+
+        let body = self.read_body(&dir_entry_path).await?;
+
+        tracing::info!(message = "Attaching precompressed sibling", route = %base_route, ?encoding, file_size = body.len(), ?dir_entry_path);
+
+        let etag = etag::compute(&body);
+
+        let mut res = http::Response::new(bytes::Bytes::from(body));
+        *res.headers_mut() = base.identity.headers().clone();
+        res.headers_mut()
+            .insert(http::header::CONTENT_ENCODING, encoding.header_value());
+        res.headers_mut().insert(http::header::ETAG, etag);
+
+        // The variant produced by the build takes precedence over the one we could make.
+        base.encoded
+            .retain(|(existing_encoding, _)| *existing_encoding != encoding);
+        base.encoded.push((encoding, res.into()));
+
+        finish_encoded(base);
+
+        Ok(None)
+    }
+
+    /// Apply the header rules matching the route or the file path to the headers.
+    fn apply_header_rules(
+        &self,
+        route: &str,
+        dir_entry_path: &std::path::Path,
+        headers: &mut http::HeaderMap,
+    ) {
+        if self.header_rules.0.is_empty() {
+            return;
+        }
+
+        let file_path = dir_entry_path
+            .strip_prefix(&self.root_dir)
+            .ok()
+            .and_then(std::path::Path::to_str)
+            .map(|file_path| format!("/{file_path}"));
+
+        let mut paths = vec![route];
+        if let Some(file_path) = file_path.as_deref() {
+            if file_path != route {
+                paths.push(file_path);
+            }
+        }
+
+        self.header_rules.apply(&paths, headers);
+    }
+
+    /// The route the given mount-relative route is served at.
+    fn mounted(&self, route: &str) -> String {
+        match (self.mount_prefix.as_str(), route) {
+            ("", route) => route.to_owned(),
+            (prefix, "/") => prefix.to_owned(),
+            (prefix, route) => format!("{prefix}{route}"),
+        }
+    }
+
+    /// Check whether the file at the given path relative to the root dir is a control file.
+    fn is_control_file(&self, route_path: &std::path::Path) -> bool {
+        let serve_json =
+            self.serve_json && route_path == std::path::Path::new(serve_json::FILE_NAME);
+        let netlify_file = self.netlify_files
+            && (route_path == std::path::Path::new(netlify_files::HEADERS_FILE)
+                || route_path == std::path::Path::new(netlify_files::REDIRECTS_FILE));
+        serve_json || netlify_file
+    }
+
+    /// Check whether the templating is configured for the given route.
+    fn is_templated(&self, route: &str) -> bool {
+        match route {
+            "/" => self.root_templating.is_some() || self.base_href,
+            "/config.json" => self.config_json_templating.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Read the control file contents.
+    async fn read_control_file(
+        &self,
+        dir_entry_path: &std::path::Path,
+    ) -> Result<String, LoadError> {
+        tokio::fs::read_to_string(dir_entry_path)
+            .await
+            .map_err(|err| LoadError::ReadingControlFile(dir_entry_path.to_path_buf(), err))
+    }
+
+    /// Read the response body from the file, respecting the max file size.
+    async fn read_body(&self, dir_entry_path: &std::path::Path) -> Result<Vec<u8>, LoadError> {
+        let body = match tokio::fs::read(dir_entry_path).await {
+            Ok(data) => data,
+            Err(err) => return Err(LoadError::ReadingBody(dir_entry_path.to_path_buf(), err)),
+        };
+
+        let file_size: FileSize = body.len().try_into().unwrap();
+        if file_size > self.max_file_size {
+            return Err(LoadError::MaxFileSizeExceeded(
+                dir_entry_path.to_path_buf(),
+                file_size,
+            ));
+        }
+
+        Ok(body)
+    }
+
+    /// Add the precompressed representations to the route.
+    ///
+    /// The compression is CPU-heavy, so it runs on the blocking threads to keep the runtime
+    /// serving the requests while the site is reloaded.
+    async fn precompress(
+        &self,
+        route: &mut mem_server::Route,
+        dir_entry_path: &std::path::Path,
+    ) -> Result<(), LoadError> {
+        let identity_body = route.identity.body().clone();
+        let identity_len = identity_body.len();
+        let encodings = self.precompress.clone();
+
+        let compressed = tokio::task::spawn_blocking(move || {
+            encodings
+                .into_iter()
+                .map(|encoding| {
+                    precompress::compress(encoding, &identity_body)
+                        .map(|compressed| (encoding, compressed))
+                        .map_err(|err| (encoding, err))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
+        .map_err(|(encoding, err)| {
+            LoadError::Compression(dir_entry_path.to_path_buf(), encoding, err)
+        })?;
+
+        for (encoding, compressed) in compressed {
+            if compressed.len() >= identity_len {
+                tracing::debug!(
+                    message = "Skipping the ineffective encoding",
+                    ?encoding,
+                    ?dir_entry_path
+                );
+                continue;
+            }
+
+            tracing::debug!(
+                message = "Adding encoded variant",
+                ?encoding,
+                size = compressed.len(),
+                ?dir_entry_path
+            );
+
+            let etag = etag::compute(&compressed);
+
+            let mut res = http::Response::new(bytes::Bytes::from(compressed));
+            *res.headers_mut() = route.identity.headers().clone();
+            res.headers_mut()
+                .insert(http::header::CONTENT_ENCODING, encoding.header_value());
+            res.headers_mut().insert(http::header::ETAG, etag);
+
+            route.encoded.push((encoding, res.into()));
+        }
+
+        finish_encoded(route);
+
+        Ok(())
+    }
+}
+
+/// Order the encoded representations of the route and mark all the representations as varying
+/// by the `Accept-Encoding`.
+fn finish_encoded(route: &mut mem_server::Route) {
+    if route.encoded.is_empty() {
+        return;
+    }
+
+    // Prefer the smallest representation among the equally acceptable ones.
+    route.encoded.sort_by_key(|(_, res)| res.body().len());
+
+    let vary = http::HeaderValue::from_static("accept-encoding");
+    for res in route.responses_mut() {
+        let already_set = res
+            .headers()
+            .get_all(http::header::VARY)
+            .iter()
+            .any(|val| val == vary);
+        if !already_set {
+            res.headers_mut().append(http::header::VARY, vary.clone());
+        }
+    }
+}
+
+/// A file with the precompressed content of another file, placed next to it by the build.
+#[derive(Debug)]
+struct PrecompressedSibling {
+    /// The route the file would have on its own.
+    route: String,
+
+    /// The route of the file with the original content.
+    base_route: String,
+
+    /// The encoding of the content.
+    encoding: content_encoding::Encoding,
+}
+
+impl PrecompressedSibling {
+    /// The extensions of the precompressed siblings.
+    const EXTENSIONS: [&str; 3] = ["br", "gz", "zst"];
+
+    /// Detect the precompressed sibling by its path relative to the root dir.
+    fn detect(route_path: &std::path::Path) -> Result<Option<Self>, LoadError> {
+        let encoding = match route_path.extension().and_then(|ext| ext.to_str()) {
+            Some("br") => content_encoding::Encoding::Brotli,
+            Some("gz") => content_encoding::Encoding::Gzip,
+            Some("zst") => content_encoding::Encoding::Zstd,
+            _ => return Ok(None),
+        };
+
+        let base_route_path = route_path.with_extension("");
+
+        let route = route_from_file_path::convert(route_path)
+            .map_err(|err| LoadError::RouteConversion(route_path.to_path_buf(), err))?;
+        let base_route = route_from_file_path::convert(&base_route_path)
+            .map_err(|err| LoadError::RouteConversion(base_route_path, err))?;
+
+        Ok(Some(Self {
+            route,
+            base_route,
+            encoding,
+        }))
+    }
+}
+
+/// The file path relative to the root dir with the leading slash, as matched by
+/// the `serve.json` header sources.
+fn file_path(route_path: &std::path::Path) -> String {
+    format!("/{}", route_path.to_string_lossy())
+}
+
+/// Check whether the content type is HTML.
+fn is_html(content_type: Option<&http::HeaderValue>) -> bool {
+    content_type.is_some_and(|content_type| content_type.as_bytes().starts_with(b"text/html"))
 }
@@ -11,6 +11,9 @@ pub enum Error {
 }
 
 /// Get a route from the file path.
+///
+/// The route is the decoded path, like `/my file.png`, which the percent-encoded request paths
+/// are normalized to before the lookup.
 pub fn convert(file_path: &Path) -> Result<String, Error> {
     tracing::debug!(message = "Preparing route", ?file_path);
 
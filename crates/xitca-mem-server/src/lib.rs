@@ -1,18 +1,71 @@
 //! The [`xitca_web`] integration for the [`mem_server::MemServer`].
 
-use std::sync::Arc;
+pub mod live_reload;
 
-use xitca_web::http::{WebRequest, WebResponse};
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use arc_swap::ArcSwap;
+use live_reload::LiveReload;
+use xitca_web::{
+    body::ResponseBody,
+    bytes::Bytes,
+    http::{response::Parts, WebRequest, WebResponse},
+};
 
 /// The [`xitca_web`] integration for the [`mem_server::MemServer`].
-pub struct Service(pub Arc<mem_server::MemServer>);
+///
+/// The server can be swapped while serving: the requests in flight keep the server they
+/// started with, and the new requests get the new one.
+#[derive(Clone)]
+pub struct Service {
+    /// The server to handle the requests with.
+    server: Arc<ArcSwap<mem_server::MemServer>>,
+
+    /// The live reload notifier to serve the events endpoint for.
+    live_reload: Option<LiveReload>,
+}
+
+impl Service {
+    /// Create a new service serving the given server.
+    pub fn new(server: mem_server::MemServer) -> Self {
+        Self {
+            server: Arc::new(ArcSwap::from_pointee(server)),
+            live_reload: None,
+        }
+    }
+
+    /// Serve the live reload events at the [`live_reload::EVENTS_PATH`].
+    pub fn with_live_reload(mut self, live_reload: LiveReload) -> Self {
+        self.live_reload = Some(live_reload);
+        self
+    }
+
+    /// The server the new requests are served with.
+    pub fn server(&self) -> Arc<mem_server::MemServer> {
+        self.server.load_full()
+    }
+
+    /// Replace the server to serve the new requests with, telling the live reload clients
+    /// to reload.
+    pub fn swap(&self, server: mem_server::MemServer) {
+        self.server.store(Arc::new(server));
+        if let Some(live_reload) = &self.live_reload {
+            live_reload.notify();
+        }
+    }
+}
 
 impl xitca_web::service::Service for Service {
     type Response = Self;
     type Error = std::convert::Infallible;
 
     async fn call(&self, _req: ()) -> Result<Self::Response, Self::Error> {
-        Ok(Self(Arc::clone(&self.0)))
+        Ok(self.clone())
     }
 }
 
@@ -29,6 +82,112 @@ impl xitca_web::service::Service<WebRequest> for Service {
 
     #[inline]
     async fn call(&self, req: WebRequest) -> Result<Self::Response, Self::Error> {
-        Ok(self.0.handle_request(req))
+        if let Some(live_reload) = &self.live_reload {
+            if req.method() == xitca_web::http::Method::GET
+                && req.uri().path() == live_reload::EVENTS_PATH
+            {
+                return Ok(live_reload.response());
+            }
+        }
+
+        let peer = req.body().socket_addr().ip();
+        let server = self.server.load();
+        let (head, body) = server.handle_request_from(req, Some(peer)).into_parts();
+        Ok(web_response(head, response_body(body)))
+    }
+}
+
+/// Build the response with the given head.
+///
+/// The HTTP server owns the response headers, so the head shared with the route table is not
+/// moved, but its names and values are put into the new response map, which only bumps their
+/// refcounts. The map storage is the only allocation for serving a prepared route as-is.
+fn web_response(head: Arc<Parts>, body: ResponseBody) -> WebResponse {
+    match Arc::try_unwrap(head) {
+        Ok(head) => WebResponse::from_parts(head, body),
+        Err(head) => {
+            let mut res = WebResponse::new(body);
+            *res.status_mut() = head.status;
+            let headers = res.headers_mut();
+            headers.reserve(head.headers.len());
+            headers.extend(
+                head.headers
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone())),
+            );
+            res
+        }
+    }
+}
+
+/// Convert the [`mem_server::Body`] into the [`ResponseBody`].
+fn response_body(body: mem_server::Body) -> ResponseBody {
+    match body {
+        mem_server::Body::None => ResponseBody::none(),
+        mem_server::Body::Full(bytes) => ResponseBody::bytes(bytes),
+        mem_server::Body::Chunks(chunks) => ResponseBody::box_stream(ChunksStream(chunks.into())),
+    }
+}
+
+/// A stream of the preloaded body chunks.
+///
+/// Reports the exact size so that the `Content-Length` is set for the response.
+struct ChunksStream(VecDeque<Bytes>);
+
+impl futures_core::Stream for ChunksStream {
+    type Item = Result<Bytes, std::convert::Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().0.pop_front().map(Ok))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.0.iter().map(Bytes::len).sum();
+        (size, Some(size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xitca_web::{
+        http::{header, HeaderValue, Request, RequestExt, StatusCode},
+        service::Service as _,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn hits_share_the_prepared_headers() {
+        let mut res = xitca_web::http::Response::new(Bytes::from_static(b"root"));
+        res.headers_mut().insert(
+            header::CACHE_CONTROL,
+            HeaderValue::try_from(String::from("public, max-age=60")).unwrap(),
+        );
+        res.headers_mut().insert(
+            "x-custom",
+            HeaderValue::try_from(String::from("value")).unwrap(),
+        );
+
+        let mut server = mem_server::MemServer::default();
+        server.routes.insert("/".into(), res.into());
+        let service = Service::new(server);
+
+        let req = Request::builder()
+            .uri("/")
+            .body(RequestExt::default())
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let server = service.server.load();
+        let prepared = server.routes.get("/").unwrap().identity.headers();
+        assert_eq!(res.headers(), prepared);
+        for name in [header::CACHE_CONTROL.as_str(), "x-custom", "accept-ranges"] {
+            assert_eq!(
+                res.headers()[name].as_bytes().as_ptr(),
+                prepared[name].as_bytes().as_ptr(),
+                "{name}"
+            );
+        }
     }
 }
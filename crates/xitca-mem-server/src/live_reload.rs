@@ -0,0 +1,112 @@
+//! The live reload notifications over the server-sent events.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::sync::broadcast;
+use xitca_web::{body::ResponseBody, bytes::Bytes, http::WebResponse};
+
+/// The path of the server-sent events endpoint.
+pub const EVENTS_PATH: &str = "/__sap/live-reload";
+
+/// The client script reloading the page on the reload events.
+pub const CLIENT_SCRIPT: &str =
+    r#"new EventSource("/__sap/live-reload").addEventListener("reload", () => location.reload());"#;
+
+/// The event sent to the clients when the site is reloaded.
+const RELOAD_EVENT: &[u8] = b"event: reload\ndata: \n\n";
+
+/// The comment sent to the clients when they connect, so that the response starts right away.
+const CONNECTED_COMMENT: &[u8] = b": connected\n\n";
+
+/// The live reload notifier.
+#[derive(Debug, Clone)]
+pub struct LiveReload(broadcast::Sender<()>);
+
+impl Default for LiveReload {
+    fn default() -> Self {
+        Self(broadcast::channel(1).0)
+    }
+}
+
+impl LiveReload {
+    /// Tell the connected clients to reload.
+    pub fn notify(&self) {
+        // There might be no clients connected, which is fine.
+        let _ = self.0.send(());
+    }
+
+    /// Respond with the stream of the reload events.
+    pub(crate) fn response(&self) -> WebResponse {
+        let mut res = WebResponse::new(ResponseBody::box_stream(Events::new(self.0.subscribe())));
+        let headers = res.headers_mut();
+        headers.insert(
+            xitca_web::http::header::CONTENT_TYPE,
+            xitca_web::http::HeaderValue::from_static("text/event-stream"),
+        );
+        headers.insert(
+            xitca_web::http::header::CACHE_CONTROL,
+            xitca_web::http::HeaderValue::from_static("no-cache"),
+        );
+        res
+    }
+}
+
+/// The future of the next event, along with the receiver to wait for the one after it.
+type NextEvent = Pin<Box<dyn Future<Output = (broadcast::Receiver<()>, bool)>>>;
+
+/// The stream of the reload events for a single client.
+struct Events {
+    /// Whether the connected comment is sent.
+    connected: bool,
+
+    /// The next event.
+    next: NextEvent,
+}
+
+impl Events {
+    /// Create the stream of the events from the receiver.
+    fn new(receiver: broadcast::Receiver<()>) -> Self {
+        Self {
+            connected: false,
+            next: next_event(receiver),
+        }
+    }
+}
+
+/// Wait for the next event, which is `false` when the notifier is gone.
+fn next_event(mut receiver: broadcast::Receiver<()>) -> NextEvent {
+    Box::pin(async move {
+        // The missed events collapse into a single reload.
+        let received = !matches!(
+            receiver.recv().await,
+            Err(broadcast::error::RecvError::Closed)
+        );
+        (receiver, received)
+    })
+}
+
+impl futures_core::Stream for Events {
+    type Item = Result<Bytes, std::convert::Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if !this.connected {
+            this.connected = true;
+            return Poll::Ready(Some(Ok(Bytes::from_static(CONNECTED_COMMENT))));
+        }
+
+        match this.next.as_mut().poll(cx) {
+            Poll::Ready((receiver, true)) => {
+                this.next = next_event(receiver);
+                Poll::Ready(Some(Ok(Bytes::from_static(RELOAD_EVENT))))
+            }
+            Poll::Ready((_, false)) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
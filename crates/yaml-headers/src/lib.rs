@@ -1,5 +1,7 @@
 //! A simple utility for parsing the HTTP headers from YAML.
 
+pub mod rules;
+
 use std::str::FromStr;
 
 /// HTTP headers wrapper.
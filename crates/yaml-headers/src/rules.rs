@@ -0,0 +1,380 @@
+//! Per-path header rules.
+//!
+//! The rules are listed in a YAML sequence and applied in order, with every matching rule
+//! applied on top of the previous ones:
+//!
+//! ```yaml
+//! - glob: /assets/**
+//!   set:
+//!     cache-control: public, max-age=31536000, immutable
+//! - glob: /index.html
+//!   set:
+//!     cache-control: no-cache
+//! - regex: ^/fonts/.*\.woff2$
+//!   append:
+//!     access-control-allow-origin: "*"
+//! - glob: /sw.js
+//!   set:
+//!     service-worker-allowed: /
+//!   remove:
+//!     - x-frame-options
+//! ```
+
+use std::str::FromStr;
+
+use crate::Headers;
+
+/// An error that can occur while building a rule.
+#[derive(Debug, thiserror::Error)]
+pub enum RuleError {
+    /// Neither or both of the matchers were specified.
+    #[error("exactly one of the glob or regex matchers must be specified")]
+    Matcher,
+
+    /// The glob is invalid.
+    #[error("invalid glob {0:?}: {1}")]
+    Glob(String, regex::Error),
+
+    /// The regex is invalid.
+    #[error("invalid regex: {0}")]
+    Regex(regex::Error),
+
+    /// The header name to remove is invalid.
+    #[error("invalid header name to remove {0:?}: {1}")]
+    HeaderName(String, http::header::InvalidHeaderName),
+}
+
+/// A list of the header rules.
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Rules(pub Vec<Rule>);
+
+impl FromStr for Rules {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let val: Option<Self> = serde_yaml::from_str(s)?;
+        Ok(val.unwrap_or_default())
+    }
+}
+
+impl Rules {
+    /// Apply all the rules matching any of the given paths to the headers, in order.
+    pub fn apply(&self, paths: &[&str], headers: &mut http::HeaderMap) {
+        for rule in &self.0 {
+            if paths.iter().any(|path| rule.matcher.is_match(path)) {
+                rule.apply(headers);
+            }
+        }
+    }
+}
+
+/// A header rule.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(try_from = "RawRule")]
+pub struct Rule {
+    /// The matcher for the paths to apply the rule to.
+    pub matcher: Matcher,
+
+    /// The headers to remove.
+    pub remove: Vec<http::HeaderName>,
+
+    /// The headers to set, replacing the existing values.
+    pub set: http::HeaderMap,
+
+    /// The headers to append to the existing values.
+    pub append: http::HeaderMap,
+}
+
+impl Rule {
+    /// Apply the rule to the headers.
+    ///
+    /// The headers are removed first, then set, and then appended.
+    pub fn apply(&self, headers: &mut http::HeaderMap) {
+        for name in &self.remove {
+            headers.remove(name);
+        }
+
+        headers.extend(self.set.clone());
+
+        for (name, value) in &self.append {
+            headers.append(name, value.clone());
+        }
+    }
+}
+
+/// The paths matcher.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// The glob matcher, compiled into an anchored regex.
+    ///
+    /// The `*` and `?` do not cross the `/` and the `**` does, `[...]` matches a character
+    /// class and `{a,b}` matches any of the alternatives.
+    Glob(regex::Regex),
+
+    /// The regex matcher, unanchored unless the anchors are used explicitly.
+    Regex(regex::Regex),
+}
+
+impl Matcher {
+    /// Build the glob matcher.
+    pub fn glob(glob: &str) -> Result<Self, RuleError> {
+        let regex = regex::Regex::new(&glob_to_regex(glob))
+            .map_err(|err| RuleError::Glob(glob.to_owned(), err))?;
+        Ok(Self::Glob(regex))
+    }
+
+    /// Check whether the path matches.
+    pub fn is_match(&self, path: &str) -> bool {
+        match self {
+            Self::Glob(glob) => glob.is_match(path),
+            Self::Regex(regex) => regex.is_match(path),
+        }
+    }
+}
+
+/// The rule as it is written in the YAML.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    /// The glob to match the paths with.
+    glob: Option<String>,
+
+    /// The regex to match the paths with.
+    regex: Option<String>,
+
+    /// The names of the headers to remove.
+    #[serde(default)]
+    remove: Vec<String>,
+
+    /// The headers to set.
+    #[serde(default)]
+    set: Option<Headers>,
+
+    /// The headers to append.
+    #[serde(default)]
+    append: Option<Headers>,
+}
+
+impl TryFrom<RawRule> for Rule {
+    type Error = RuleError;
+
+    fn try_from(raw: RawRule) -> Result<Self, Self::Error> {
+        let matcher = match (raw.glob, raw.regex) {
+            (Some(glob), None) => Matcher::glob(&glob)?,
+            (None, Some(regex)) => {
+                Matcher::Regex(regex::Regex::new(&regex).map_err(RuleError::Regex)?)
+            }
+            _ => return Err(RuleError::Matcher),
+        };
+
+        let remove = raw
+            .remove
+            .into_iter()
+            .map(|name| {
+                http::HeaderName::from_str(&name).map_err(|err| RuleError::HeaderName(name, err))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            matcher,
+            remove,
+            set: raw.set.map(Into::into).unwrap_or_default(),
+            append: raw.append.map(Into::into).unwrap_or_default(),
+        })
+    }
+}
+
+/// Translate the glob into the anchored regex.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::with_capacity(glob.len() * 2 + 2);
+    regex.push('^');
+
+    let mut chars = glob.chars().peekable();
+    let mut in_alternatives = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    // The `**/` matches zero or more whole segments.
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if matches!(chars.peek(), Some('!' | '^')) {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            '{' if !in_alternatives => {
+                in_alternatives = true;
+                regex.push_str("(?:");
+            }
+            ',' if in_alternatives => regex.push('|'),
+            '}' if in_alternatives => {
+                in_alternatives = false;
+                regex.push(')');
+            }
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+- glob: /**
+  set:
+    x-frame-options: DENY
+    cache-control: no-cache
+- glob: /assets/**
+  set:
+    cache-control: public, max-age=31536000, immutable
+- regex: ^/fonts/.*\.woff2$
+  append:
+    access-control-allow-origin: "*"
+    vary: origin
+- glob: /sw.js
+  set:
+    service-worker-allowed: /
+  remove:
+    - x-frame-options
+"#;
+
+    fn apply(rules: &Rules, path: &str) -> Vec<(String, String)> {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(http::header::VARY, "accept-encoding".parse().unwrap());
+        rules.apply(&[path], &mut headers);
+
+        let mut headers: Vec<_> = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_str().unwrap().to_owned()))
+            .collect();
+        headers.sort();
+        headers
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        let mut pairs: Vec<_> = pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn applying() {
+        let rules = Rules::from_str(SAMPLE).unwrap();
+
+        assert_eq!(
+            apply(&rules, "/"),
+            pairs(&[
+                ("cache-control", "no-cache"),
+                ("vary", "accept-encoding"),
+                ("x-frame-options", "DENY"),
+            ])
+        );
+        assert_eq!(
+            apply(&rules, "/assets/js/main.js"),
+            pairs(&[
+                ("cache-control", "public, max-age=31536000, immutable"),
+                ("vary", "accept-encoding"),
+                ("x-frame-options", "DENY"),
+            ])
+        );
+        assert_eq!(
+            apply(&rules, "/fonts/a.woff2"),
+            pairs(&[
+                ("access-control-allow-origin", "*"),
+                ("cache-control", "no-cache"),
+                ("vary", "accept-encoding"),
+                ("vary", "origin"),
+                ("x-frame-options", "DENY"),
+            ])
+        );
+        assert_eq!(
+            apply(&rules, "/sw.js"),
+            pairs(&[
+                ("cache-control", "no-cache"),
+                ("service-worker-allowed", "/"),
+                ("vary", "accept-encoding"),
+            ])
+        );
+    }
+
+    #[test]
+    fn globs() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("/assets/*.js", "/assets/main.js", true),
+            ("/assets/*.js", "/assets/js/main.js", false),
+            ("/assets/*.js", "/assets/main.css", false),
+            ("/assets/**", "/assets/js/main.js", true),
+            ("/assets/**", "/assetsx/main.js", false),
+            ("/**/*.woff2", "/a.woff2", true),
+            ("/**/*.woff2", "/fonts/x/a.woff2", true),
+            ("/*.{woff,woff2}", "/a.woff", true),
+            ("/*.{woff,woff2}", "/a.woff2", true),
+            ("/*.{woff,woff2}", "/a.ttf", false),
+            ("/file?.txt", "/file1.txt", true),
+            ("/file?.txt", "/file/.txt", false),
+            ("/[ab].txt", "/a.txt", true),
+            ("/[!ab].txt", "/a.txt", false),
+            ("/index.html", "/index.html", true),
+            ("/index.html", "/indexxhtml", false),
+        ];
+
+        for (glob, path, expected) in cases {
+            let rules = Rules::from_str(&format!("- glob: \"{glob}\"")).unwrap();
+            assert_eq!(
+                rules.0[0].matcher.is_match(path),
+                *expected,
+                "{glob} {path}"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let cases = [
+            "- set: {a: b}",
+            "- glob: /a\n  regex: ^/a",
+            "- glob: /a/[b-a]",
+            "- regex: ^/a(",
+            "- glob: /a\n  remove: [\"bad header\"]",
+            "- glob: /a\n  unknown: 1",
+        ];
+
+        for sample in cases {
+            assert!(Rules::from_str(sample).is_err(), "{sample}");
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert!(Rules::from_str("").unwrap().0.is_empty());
+    }
+}
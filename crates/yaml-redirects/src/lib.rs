@@ -0,0 +1,207 @@
+//! A simple utility for parsing the redirect and rewrite rules from YAML.
+//!
+//! The rules are listed in a YAML sequence and evaluated in order, with the first matching
+//! rule winning:
+//!
+//! ```yaml
+//! - from: /old-blog/*
+//!   to: /blog/:splat
+//!   status: 308
+//! - from: /search
+//!   to: https://search.example.com/
+//!   status: 302
+//!   query: drop
+//! - from: /app/*
+//!   to: /app/index.html
+//!   status: 200
+//!   force: false
+//! ```
+//!
+//! The redirect statuses redirect to the target, while the others rewrite the request to
+//! the target route served with the given status. The status is `301` by default.
+//!
+//! The rules are forced by default, meaning they apply even when there is a route at the path;
+//! the `force: false` rules only apply when there is none.
+
+use std::str::FromStr;
+
+/// An error that can occur while building a rule.
+#[derive(Debug, thiserror::Error)]
+pub enum RuleError {
+    /// The status code is invalid.
+    #[error("invalid status {0}")]
+    Status(u16),
+
+    /// The rule is invalid.
+    #[error("rule from {0:?}: {1}")]
+    Rule(String, mem_server::RedirectError),
+}
+
+/// The query handling.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Query {
+    /// Pass the request query on to the redirect target.
+    #[default]
+    Preserve,
+
+    /// Drop the request query.
+    Drop,
+}
+
+/// A list of the redirect and rewrite rules.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Redirects(pub Vec<Rule>);
+
+impl FromStr for Redirects {
+    type Err = serde_yaml::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let val: Option<Self> = serde_yaml::from_str(s)?;
+        Ok(val.unwrap_or_default())
+    }
+}
+
+impl From<Redirects> for mem_server::Redirects {
+    fn from(value: Redirects) -> Self {
+        Self(value.0.into_iter().map(|rule| rule.0).collect())
+    }
+}
+
+/// A redirect or rewrite rule.
+#[derive(Debug)]
+pub struct Rule(pub mem_server::RedirectRule);
+
+impl<'de> serde::Deserialize<'de> for Rule {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = RawRule::deserialize(deserializer)?;
+        raw.try_into().map_err(serde::de::Error::custom)
+    }
+}
+
+/// The rule as it is written in the YAML.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    /// The pattern of the paths to apply the rule to.
+    from: String,
+
+    /// The redirect or rewrite target.
+    to: String,
+
+    /// The status to respond with.
+    #[serde(default = "default_status")]
+    status: u16,
+
+    /// Whether to apply the rule even when there is a route at the path.
+    #[serde(default = "default_force")]
+    force: bool,
+
+    /// The query handling.
+    #[serde(default)]
+    query: Query,
+}
+
+/// The default status.
+fn default_status() -> u16 {
+    301
+}
+
+/// The default for the rules forcing.
+fn default_force() -> bool {
+    true
+}
+
+impl TryFrom<RawRule> for Rule {
+    type Error = RuleError;
+
+    fn try_from(raw: RawRule) -> Result<Self, Self::Error> {
+        let status =
+            http::StatusCode::from_u16(raw.status).map_err(|_| RuleError::Status(raw.status))?;
+
+        let mut rule = raw
+            .from
+            .parse()
+            .and_then(|from| mem_server::RedirectRule::new(from, raw.to, status, raw.force))
+            .map_err(|err| RuleError::Rule(raw.from, err))?;
+        rule.preserve_query = raw.query == Query::Preserve;
+
+        Ok(Self(rule))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "
+- from: /old-blog/*
+  to: /blog/:splat
+  status: 308
+- from: /search
+  to: https://search.example.com/
+  status: 302
+  query: drop
+- from: /app/*
+  to: /app/index.html
+  status: 200
+  force: false
+- from: /home
+  to: /
+";
+
+    #[test]
+    fn parsing() {
+        let redirects: mem_server::Redirects = Redirects::from_str(SAMPLE).unwrap().into();
+
+        let rules: Vec<_> = redirects
+            .0
+            .iter()
+            .map(|rule| {
+                (
+                    rule.to.as_str(),
+                    rule.status.as_u16(),
+                    rule.force,
+                    rule.preserve_query,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            rules,
+            vec![
+                ("/blog/:splat", 308, true, true),
+                ("https://search.example.com/", 302, true, false),
+                ("/app/index.html", 200, false, true),
+                ("/", 301, true, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let cases = [
+            "- from: /a",
+            "- to: /a",
+            "- from: a\n  to: /b",
+            "- from: /a\n  to: /b\n  status: 1000",
+            "- from: /a\n  to: /b\n  status: 100",
+            "- from: /a\n  to: https://example.com\n  status: 200",
+            "- from: /a\n  to: /b\n  query: keep",
+            "- from: /a\n  to: /b\n  unknown: 1",
+        ];
+
+        for sample in cases {
+            assert!(Redirects::from_str(sample).is_err(), "{sample}");
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert!(Redirects::from_str("").unwrap().0.is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+export function f0(a, b) { return a + b + 0; }
+export function f1(a, b) { return a + b + 1; }
+export function f2(a, b) { return a + b + 2; }
+export function f3(a, b) { return a + b + 3; }
+export function f4(a, b) { return a + b + 4; }
+export function f5(a, b) { return a + b + 5; }
+export function f6(a, b) { return a + b + 6; }
+export function f7(a, b) { return a + b + 7; }
+export function f8(a, b) { return a + b + 8; }
+export function f9(a, b) { return a + b + 9; }
+export function f10(a, b) { return a + b + 10; }
+export function f11(a, b) { return a + b + 11; }
+export function f12(a, b) { return a + b + 12; }
+export function f13(a, b) { return a + b + 13; }
+export function f14(a, b) { return a + b + 14; }
+export function f15(a, b) { return a + b + 15; }
+export function f16(a, b) { return a + b + 16; }
+export function f17(a, b) { return a + b + 17; }
+export function f18(a, b) { return a + b + 18; }
+export function f19(a, b) { return a + b + 19; }
+export function f20(a, b) { return a + b + 20; }
+export function f21(a, b) { return a + b + 21; }
+export function f22(a, b) { return a + b + 22; }
+export function f23(a, b) { return a + b + 23; }
+export function f24(a, b) { return a + b + 24; }
+export function f25(a, b) { return a + b + 25; }
+export function f26(a, b) { return a + b + 26; }
+export function f27(a, b) { return a + b + 27; }
+export function f28(a, b) { return a + b + 28; }
+export function f29(a, b) { return a + b + 29; }
+export function f30(a, b) { return a + b + 30; }
+export function f31(a, b) { return a + b + 31; }
+export function f32(a, b) { return a + b + 32; }
+export function f33(a, b) { return a + b + 33; }
+export function f34(a, b) { return a + b + 34; }
+export function f35(a, b) { return a + b + 35; }
+export function f36(a, b) { return a + b + 36; }
+export function f37(a, b) { return a + b + 37; }
+export function f38(a, b) { return a + b + 38; }
+export function f39(a, b) { return a + b + 39; }
+export function f40(a, b) { return a + b + 40; }
+export function f41(a, b) { return a + b + 41; }
+export function f42(a, b) { return a + b + 42; }
+export function f43(a, b) { return a + b + 43; }
+export function f44(a, b) { return a + b + 44; }
+export function f45(a, b) { return a + b + 45; }
+export function f46(a, b) { return a + b + 46; }
+export function f47(a, b) { return a + b + 47; }
+export function f48(a, b) { return a + b + 48; }
+export function f49(a, b) { return a + b + 49; }
+export function f50(a, b) { return a + b + 50; }
+export function f51(a, b) { return a + b + 51; }
+export function f52(a, b) { return a + b + 52; }
+export function f53(a, b) { return a + b + 53; }
+export function f54(a, b) { return a + b + 54; }
+export function f55(a, b) { return a + b + 55; }
+export function f56(a, b) { return a + b + 56; }
+export function f57(a, b) { return a + b + 57; }
+export function f58(a, b) { return a + b + 58; }
+export function f59(a, b) { return a + b + 59; }
+export function f60(a, b) { return a + b + 60; }
+export function f61(a, b) { return a + b + 61; }
+export function f62(a, b) { return a + b + 62; }
+export function f63(a, b) { return a + b + 63; }
+export function f64(a, b) { return a + b + 64; }
+export function f65(a, b) { return a + b + 65; }
+export function f66(a, b) { return a + b + 66; }
+export function f67(a, b) { return a + b + 67; }
+export function f68(a, b) { return a + b + 68; }
+export function f69(a, b) { return a + b + 69; }
+export function f70(a, b) { return a + b + 70; }
+export function f71(a, b) { return a + b + 71; }
+export function f72(a, b) { return a + b + 72; }
+export function f73(a, b) { return a + b + 73; }
+export function f74(a, b) { return a + b + 74; }
+export function f75(a, b) { return a + b + 75; }
+export function f76(a, b) { return a + b + 76; }
+export function f77(a, b) { return a + b + 77; }
+export function f78(a, b) { return a + b + 78; }
+export function f79(a, b) { return a + b + 79; }
+export function f80(a, b) { return a + b + 80; }
+export function f81(a, b) { return a + b + 81; }
+export function f82(a, b) { return a + b + 82; }
+export function f83(a, b) { return a + b + 83; }
+export function f84(a, b) { return a + b + 84; }
+export function f85(a, b) { return a + b + 85; }
+export function f86(a, b) { return a + b + 86; }
+export function f87(a, b) { return a + b + 87; }
+export function f88(a, b) { return a + b + 88; }
+export function f89(a, b) { return a + b + 89; }
+export function f90(a, b) { return a + b + 90; }
+export function f91(a, b) { return a + b + 91; }
+export function f92(a, b) { return a + b + 92; }
+export function f93(a, b) { return a + b + 93; }
+export function f94(a, b) { return a + b + 94; }
+export function f95(a, b) { return a + b + 95; }
+export function f96(a, b) { return a + b + 96; }
+export function f97(a, b) { return a + b + 97; }
+export function f98(a, b) { return a + b + 98; }
+export function f99(a, b) { return a + b + 99; }
+export function f100(a, b) { return a + b + 100; }
+export function f101(a, b) { return a + b + 101; }
+export function f102(a, b) { return a + b + 102; }
+export function f103(a, b) { return a + b + 103; }
+export function f104(a, b) { return a + b + 104; }
+export function f105(a, b) { return a + b + 105; }
+export function f106(a, b) { return a + b + 106; }
+export function f107(a, b) { return a + b + 107; }
+export function f108(a, b) { return a + b + 108; }
+export function f109(a, b) { return a + b + 109; }
+export function f110(a, b) { return a + b + 110; }
+export function f111(a, b) { return a + b + 111; }
+export function f112(a, b) { return a + b + 112; }
+export function f113(a, b) { return a + b + 113; }
+export function f114(a, b) { return a + b + 114; }
+export function f115(a, b) { return a + b + 115; }
+export function f116(a, b) { return a + b + 116; }
+export function f117(a, b) { return a + b + 117; }
+export function f118(a, b) { return a + b + 118; }
+export function f119(a, b) { return a + b + 119; }
+export function f120(a, b) { return a + b + 120; }
+export function f121(a, b) { return a + b + 121; }
+export function f122(a, b) { return a + b + 122; }
+export function f123(a, b) { return a + b + 123; }
+export function f124(a, b) { return a + b + 124; }
+export function f125(a, b) { return a + b + 125; }
+export function f126(a, b) { return a + b + 126; }
+export function f127(a, b) { return a + b + 127; }
+export function f128(a, b) { return a + b + 128; }
+export function f129(a, b) { return a + b + 129; }
+export function f130(a, b) { return a + b + 130; }
+export function f131(a, b) { return a + b + 131; }
+export function f132(a, b) { return a + b + 132; }
+export function f133(a, b) { return a + b + 133; }
+export function f134(a, b) { return a + b + 134; }
+export function f135(a, b) { return a + b + 135; }
+export function f136(a, b) { return a + b + 136; }
+export function f137(a, b) { return a + b + 137; }
+export function f138(a, b) { return a + b + 138; }
+export function f139(a, b) { return a + b + 139; }
+export function f140(a, b) { return a + b + 140; }
+export function f141(a, b) { return a + b + 141; }
+export function f142(a, b) { return a + b + 142; }
+export function f143(a, b) { return a + b + 143; }
+export function f144(a, b) { return a + b + 144; }
+export function f145(a, b) { return a + b + 145; }
+export function f146(a, b) { return a + b + 146; }
+export function f147(a, b) { return a + b + 147; }
+export function f148(a, b) { return a + b + 148; }
+export function f149(a, b) { return a + b + 149; }
+export function f150(a, b) { return a + b + 150; }
+export function f151(a, b) { return a + b + 151; }
+export function f152(a, b) { return a + b + 152; }
+export function f153(a, b) { return a + b + 153; }
+export function f154(a, b) { return a + b + 154; }
+export function f155(a, b) { return a + b + 155; }
+export function f156(a, b) { return a + b + 156; }
+export function f157(a, b) { return a + b + 157; }
+export function f158(a, b) { return a + b + 158; }
+export function f159(a, b) { return a + b + 159; }
+export function f160(a, b) { return a + b + 160; }
+export function f161(a, b) { return a + b + 161; }
+export function f162(a, b) { return a + b + 162; }
+export function f163(a, b) { return a + b + 163; }
+export function f164(a, b) { return a + b + 164; }
+export function f165(a, b) { return a + b + 165; }
+export function f166(a, b) { return a + b + 166; }
+export function f167(a, b) { return a + b + 167; }
+export function f168(a, b) { return a + b + 168; }
+export function f169(a, b) { return a + b + 169; }
+export function f170(a, b) { return a + b + 170; }
+export function f171(a, b) { return a + b + 171; }
+export function f172(a, b) { return a + b + 172; }
+export function f173(a, b) { return a + b + 173; }
+export function f174(a, b) { return a + b + 174; }
+export function f175(a, b) { return a + b + 175; }
+export function f176(a, b) { return a + b + 176; }
+export function f177(a, b) { return a + b + 177; }
+export function f178(a, b) { return a + b + 178; }
+export function f179(a, b) { return a + b + 179; }
+export function f180(a, b) { return a + b + 180; }
+export function f181(a, b) { return a + b + 181; }
+export function f182(a, b) { return a + b + 182; }
+export function f183(a, b) { return a + b + 183; }
+export function f184(a, b) { return a + b + 184; }
+export function f185(a, b) { return a + b + 185; }
+export function f186(a, b) { return a + b + 186; }
+export function f187(a, b) { return a + b + 187; }
+export function f188(a, b) { return a + b + 188; }
+export function f189(a, b) { return a + b + 189; }
+export function f190(a, b) { return a + b + 190; }
+export function f191(a, b) { return a + b + 191; }
+export function f192(a, b) { return a + b + 192; }
+export function f193(a, b) { return a + b + 193; }
+export function f194(a, b) { return a + b + 194; }
+export function f195(a, b) { return a + b + 195; }
+export function f196(a, b) { return a + b + 196; }
+export function f197(a, b) { return a + b + 197; }
+export function f198(a, b) { return a + b + 198; }
+export function f199(a, b) { return a + b + 199; }
@@ -0,0 +1,100 @@
+export const value0 = "value 0";
+export const value1 = "value 1";
+export const value2 = "value 2";
+export const value3 = "value 3";
+export const value4 = "value 4";
+export const value5 = "value 5";
+export const value6 = "value 6";
+export const value7 = "value 7";
+export const value8 = "value 8";
+export const value9 = "value 9";
+export const value10 = "value 10";
+export const value11 = "value 11";
+export const value12 = "value 12";
+export const value13 = "value 13";
+export const value14 = "value 14";
+export const value15 = "value 15";
+export const value16 = "value 16";
+export const value17 = "value 17";
+export const value18 = "value 18";
+export const value19 = "value 19";
+export const value20 = "value 20";
+export const value21 = "value 21";
+export const value22 = "value 22";
+export const value23 = "value 23";
+export const value24 = "value 24";
+export const value25 = "value 25";
+export const value26 = "value 26";
+export const value27 = "value 27";
+export const value28 = "value 28";
+export const value29 = "value 29";
+export const value30 = "value 30";
+export const value31 = "value 31";
+export const value32 = "value 32";
+export const value33 = "value 33";
+export const value34 = "value 34";
+export const value35 = "value 35";
+export const value36 = "value 36";
+export const value37 = "value 37";
+export const value38 = "value 38";
+export const value39 = "value 39";
+export const value40 = "value 40";
+export const value41 = "value 41";
+export const value42 = "value 42";
+export const value43 = "value 43";
+export const value44 = "value 44";
+export const value45 = "value 45";
+export const value46 = "value 46";
+export const value47 = "value 47";
+export const value48 = "value 48";
+export const value49 = "value 49";
+export const value50 = "value 50";
+export const value51 = "value 51";
+export const value52 = "value 52";
+export const value53 = "value 53";
+export const value54 = "value 54";
+export const value55 = "value 55";
+export const value56 = "value 56";
+export const value57 = "value 57";
+export const value58 = "value 58";
+export const value59 = "value 59";
+export const value60 = "value 60";
+export const value61 = "value 61";
+export const value62 = "value 62";
+export const value63 = "value 63";
+export const value64 = "value 64";
+export const value65 = "value 65";
+export const value66 = "value 66";
+export const value67 = "value 67";
+export const value68 = "value 68";
+export const value69 = "value 69";
+export const value70 = "value 70";
+export const value71 = "value 71";
+export const value72 = "value 72";
+export const value73 = "value 73";
+export const value74 = "value 74";
+export const value75 = "value 75";
+export const value76 = "value 76";
+export const value77 = "value 77";
+export const value78 = "value 78";
+export const value79 = "value 79";
+export const value80 = "value 80";
+export const value81 = "value 81";
+export const value82 = "value 82";
+export const value83 = "value 83";
+export const value84 = "value 84";
+export const value85 = "value 85";
+export const value86 = "value 86";
+export const value87 = "value 87";
+export const value88 = "value 88";
+export const value89 = "value 89";
+export const value90 = "value 90";
+export const value91 = "value 91";
+export const value92 = "value 92";
+export const value93 = "value 93";
+export const value94 = "value 94";
+export const value95 = "value 95";
+export const value96 = "value 96";
+export const value97 = "value 97";
+export const value98 = "value 98";
+export const value99 = "value 99";